@@ -71,6 +71,10 @@ where
     fn get_statbar_value(&self) -> f32 {
         self.value / self.max
     }
+
+    fn get_statbar_current_max(&self) -> Option<(f32, f32)> {
+        Some((self.value, self.max))
+    }
 }
 
 #[derive(Component, Default, Reflect)]
@@ -111,15 +115,12 @@ fn spawn_demo(mut commands: Commands, asset_server: Res<AssetServer>) {
             },
             StatbarBorder::<Health>::all(Color::DARK_GRAY, 2.0),
             StatbarColorSwitch::<Health>::new(0.33, Color::RED, Color::rgb(0., 0.8, 0.)),
-            Statbar::<Magic> {
-                empty_color: Color::rgb(0.1, 0.0, 0.1),
-                length: 100.0,
-                thickness: 16.0,
-                displacement: 90. * Vec2::Y,
-                ..Default::default()
-            },
-            StatbarBorder::<Magic>::all(Color::DARK_GRAY, 2.0),
-            StatbarColorLerp::<Magic>::new(Color::rgb(0.5, 0.0, 0.5), Color::FUCHSIA),
+            StatbarLabel::<Health>::new(asset_server.load("FiraMono-Regular.ttf"))
+                .with_title("HP")
+                .with_format(StatbarLabelFormat::CurrentMax),
+            Statbar::<Magic>::default(),
+            // size, colors and border are loaded from assets/magic.statbar.ron
+            StatbarStyleHandle::<Magic>::new(asset_server.load("magic.statbar.ron")),
         ))
         .id();
 
@@ -245,6 +246,11 @@ fn main() {
             resizable: true,
             ..Default::default()
         })
+        // reload magic.statbar.ron when it is edited
+        .insert_resource(bevy::asset::AssetServerSettings {
+            watch_for_changes: true,
+            ..Default::default()
+        })
         .add_plugins(DefaultPlugins)
         .add_plugin(WorldInspectorPlugin::new())
         .register_type::<Health>()
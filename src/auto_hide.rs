@@ -0,0 +1,140 @@
+use crate::*;
+#[cfg(feature = "serialize")]
+use serde::Deserialize;
+#[cfg(feature = "serialize")]
+use serde::Serialize;
+use std::marker::PhantomData;
+
+/// Automatically shows and hides the corresponding Statbar depending on its value,
+/// fading it in and out.
+///
+/// Could be used for enemy health bars that are hidden while the enemy is at full health,
+/// and appear for a few seconds after the enemy takes damage.
+///
+/// The rules are applied in order:
+/// * `statbar.value < always_show_below` => shown
+/// * `hide_when_full` and `statbar.value == 1.0` => hidden
+/// * `hide_when_empty` and `statbar.value == 0.0` => hidden
+/// * `0.0 < show_after_change` => shown for `show_after_change` seconds after the value changes
+/// * otherwise shown
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
+pub struct StatbarAutoHide<T>
+where
+    T: 'static,
+{
+    /// hide the bar when its value is 1.0
+    pub hide_when_full: bool,
+    /// hide the bar when its value is 0.0
+    pub hide_when_empty: bool,
+    /// seconds the bar is shown for after its value changes
+    /// * 0.0 => the bar doesn't hide after its value stops changing
+    pub show_after_change: f32,
+    /// the bar is always shown when its value is less than this threshold
+    pub always_show_below: f32,
+    /// seconds taken to fade the bar in or out
+    /// * 0.0 => no fade
+    pub fade_duration: f32,
+    /// current opacity of the bar
+    alpha: f32,
+    /// seconds since the value of the bar last changed
+    since_change: f32,
+    /// value of the bar when it was last checked, NaN before the first check
+    last_value: f32,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarAutoHide<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self {
+            hide_when_full: false,
+            hide_when_empty: false,
+            show_after_change: 0.,
+            always_show_below: 0.,
+            fade_duration: 0.25,
+            alpha: 1.,
+            since_change: f32::INFINITY,
+            last_value: f32::NAN,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> StatbarAutoHide<T>
+where
+    T: 'static,
+{
+    /// Hides the bar when full, shows it for `show_after_change` seconds after its value changes,
+    /// and always shows it when its value is less than `always_show_below`.
+    pub fn new(show_after_change: f32, always_show_below: f32) -> Self {
+        Self {
+            hide_when_full: true,
+            show_after_change,
+            always_show_below,
+            ..Default::default()
+        }
+    }
+
+    /// The current opacity of the bar, between 0.0 (= hidden) and 1.0 (= shown).
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    fn is_shown(&self, value: f32) -> bool {
+        if value < self.always_show_below {
+            return true;
+        }
+        if (self.hide_when_full && 1.0 <= value) || (self.hide_when_empty && value <= 0.0) {
+            return false;
+        }
+        self.show_after_change <= 0.0 || self.since_change < self.show_after_change
+    }
+}
+
+/// Multiplies the alpha of `color` by `alpha`
+pub(crate) fn fade_color(mut color: Color, alpha: f32) -> Color {
+    let faded_alpha = color.a() * alpha;
+    color.set_a(faded_alpha);
+    color
+}
+
+pub(crate) fn update_statbar_auto_hide<T>(
+    time: Res<Time>,
+    mut auto_hide_query: Query<(&Statbar<T>, &mut StatbarAutoHide<T>)>,
+) where
+    T: 'static,
+{
+    let delta = time.delta_seconds();
+    auto_hide_query.for_each_mut(|(bar, mut auto_hide)| {
+        let value = bar.value.clamp(0., 1.);
+        if auto_hide.last_value.is_nan() {
+            auto_hide.last_value = value;
+            auto_hide.alpha = if auto_hide.is_shown(value) { 1. } else { 0. };
+            return;
+        }
+        if value != auto_hide.last_value {
+            auto_hide.last_value = value;
+            auto_hide.since_change = 0.;
+        } else {
+            auto_hide.since_change += delta;
+        }
+        let target = if auto_hide.is_shown(value) { 1. } else { 0. };
+        auto_hide.alpha = if 0. < auto_hide.fade_duration {
+            let step = delta / auto_hide.fade_duration;
+            if auto_hide.alpha < target {
+                (auto_hide.alpha + step).min(target)
+            } else {
+                (auto_hide.alpha - step).max(target)
+            }
+        } else {
+            target
+        };
+    });
+}
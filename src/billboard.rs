@@ -0,0 +1,397 @@
+use crate::auto_hide::fade_color;
+use crate::extraction::default_camera;
+use crate::extraction::statbar_outer_offset;
+use crate::extraction::statbar_outer_size;
+use crate::extraction::statbar_size;
+use crate::*;
+use bevy::asset::load_internal_asset;
+use bevy::ecs::entity::EntityMap;
+use bevy::ecs::entity::MapEntities;
+use bevy::ecs::entity::MapEntitiesError;
+use bevy::ecs::reflect::ReflectMapEntities;
+use bevy::pbr::MaterialPipeline;
+use bevy::pbr::MaterialPipelineKey;
+use bevy::reflect::TypeUuid;
+use bevy::render::mesh::MeshVertexBufferLayout;
+use bevy::render::render_resource::AsBindGroup;
+use bevy::render::render_resource::CompareFunction;
+use bevy::render::render_resource::RenderPipelineDescriptor;
+use bevy::render::render_resource::ShaderRef;
+use bevy::render::render_resource::SpecializedMeshPipelineError;
+#[cfg(feature = "serialize")]
+use serde::Deserialize;
+#[cfg(feature = "serialize")]
+use serde::Serialize;
+use std::marker::PhantomData;
+
+const BILLBOARD_SHADER_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 9466433493212431971);
+
+/// Distance between the border, back and fill quads of a billboard, in world units
+const BILLBOARD_LAYER_STEP: f32 = 0.001;
+
+/// Adds the mesh, material and shader needed to draw statbars in 3D with `StatbarBillboard`.
+///
+/// Requires the `PbrPlugin`, which is part of `DefaultPlugins`.
+pub struct StatbarBillboardPlugin;
+
+impl Plugin for StatbarBillboardPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            BILLBOARD_SHADER_HANDLE,
+            "billboard.wgsl",
+            Shader::from_wgsl
+        );
+        app.add_plugin(MaterialPlugin::<StatbarBillboardMaterial>::default());
+        let quad = app
+            .world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Mesh::from(shape::Quad::new(Vec2::ONE)));
+        app.insert_resource(StatbarBillboardMesh(quad));
+    }
+}
+
+/// Unit quad shared by every billboard
+pub(crate) struct StatbarBillboardMesh(Handle<Mesh>);
+
+/// Flat unlit color, optionally drawn on top of everything else
+#[derive(AsBindGroup, TypeUuid, Clone, Debug)]
+#[uuid = "29313396-ce17-4535-962d-25f9a02bef3c"]
+#[bind_group_data(StatbarBillboardMaterialKey)]
+pub struct StatbarBillboardMaterial {
+    #[uniform(0)]
+    pub color: Color,
+    pub depth_test: bool,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct StatbarBillboardMaterialKey {
+    depth_test: bool,
+}
+
+impl From<&StatbarBillboardMaterial> for StatbarBillboardMaterialKey {
+    fn from(material: &StatbarBillboardMaterial) -> Self {
+        Self {
+            depth_test: material.depth_test,
+        }
+    }
+}
+
+impl Material for StatbarBillboardMaterial {
+    fn fragment_shader() -> ShaderRef {
+        BILLBOARD_SHADER_HANDLE.typed().into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Blend
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayout,
+        key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        descriptor.primitive.cull_mode = None;
+        if !key.bind_group_data.depth_test {
+            if let Some(depth_stencil) = descriptor.depth_stencil.as_mut() {
+                depth_stencil.depth_compare = CompareFunction::Always;
+                depth_stencil.depth_write_enabled = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Draws the corresponding Statbar in 3D, floating above the entity and always facing the camera.
+///
+/// The length, thickness and displacement of the statbar and the thickness of its border
+/// are multiplied by `scale` to get their size in world units.
+/// The border is always drawn outside the bar in its `color`, see `StatbarBorderKind`.
+/// Requires `StatbarBillboardPlugin`.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component, MapEntities)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
+pub struct StatbarBillboard<T>
+where
+    T: 'static,
+{
+    /// world space offset from the entity's position, like the height of its mesh
+    pub offset: Vec3,
+    /// world units per statbar unit
+    pub scale: f32,
+    /// true => the bar is hidden behind meshes in front of it,
+    /// false => the bar is drawn on top of everything
+    pub depth_test: bool,
+    /// 3D camera the bar faces,
+    /// None => the active 3D camera with the highest priority
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    pub camera: Option<Entity>,
+    /// border, back and fill quads
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    quads: Option<[Entity; 3]>,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarBillboard<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(Vec3::Y)
+    }
+}
+
+impl<T> MapEntities for StatbarBillboard<T>
+where
+    T: 'static,
+{
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        map_camera_entity(&mut self.camera, entity_map);
+        Ok(())
+    }
+}
+
+impl<T> StatbarBillboard<T>
+where
+    T: 'static,
+{
+    /// A depth tested billboard where a statbar 100 units long is 1 world unit long
+    pub fn new(offset: Vec3) -> Self {
+        Self {
+            offset,
+            scale: 0.01,
+            depth_test: true,
+            camera: None,
+            quads: None,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Draw the bar on top of everything else
+    pub fn always_on_top(mut self) -> Self {
+        self.depth_test = false;
+        self
+    }
+
+    pub fn with_camera(mut self, camera: Entity) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+}
+
+/// Marks the quads that draw a `StatbarBillboard`
+#[derive(Component)]
+pub(crate) struct StatbarBillboardQuad<T>
+where
+    T: 'static,
+{
+    bar: Entity,
+    phantom: PhantomData<fn() -> T>,
+}
+
+pub(crate) fn spawn_statbar_billboards<T>(
+    mut commands: Commands,
+    mesh: Option<Res<StatbarBillboardMesh>>,
+    materials: Option<ResMut<Assets<StatbarBillboardMaterial>>>,
+    mut billboard_query: Query<(Entity, &mut StatbarBillboard<T>), Changed<StatbarBillboard<T>>>,
+) where
+    T: 'static,
+{
+    let (mesh, mut materials) = match (mesh, materials) {
+        (Some(mesh), Some(materials)) => (mesh, materials),
+        _ => return,
+    };
+    billboard_query.for_each_mut(|(bar, mut billboard)| {
+        if billboard.quads.is_some() {
+            return;
+        }
+        let depth_test = billboard.depth_test;
+        let mut spawn_quad = || {
+            commands
+                .spawn_bundle(MaterialMeshBundle {
+                    mesh: mesh.0.clone(),
+                    material: materials.add(StatbarBillboardMaterial {
+                        color: Color::NONE,
+                        depth_test,
+                    }),
+                    visibility: Visibility { is_visible: false },
+                    ..Default::default()
+                })
+                .insert(StatbarBillboardQuad::<T> {
+                    bar,
+                    phantom: PhantomData,
+                })
+                .id()
+        };
+        billboard.quads = Some([spawn_quad(), spawn_quad(), spawn_quad()]);
+    });
+}
+
+pub(crate) fn despawn_orphaned_statbar_billboards<T>(
+    mut commands: Commands,
+    quad_query: Query<(Entity, &StatbarBillboardQuad<T>)>,
+    billboard_query: Query<&StatbarBillboard<T>>,
+) where
+    T: 'static,
+{
+    despawn_orphans(
+        &mut commands,
+        &quad_query,
+        &billboard_query,
+        |quad| quad.bar,
+        |billboard, quad_entity| {
+            billboard
+                .quads
+                .is_some_and(|quads| quads.contains(&quad_entity))
+        },
+    );
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_statbar_billboards<T>(
+    materials: Option<ResMut<Assets<StatbarBillboardMaterial>>>,
+    colorblind_mode: Option<Res<StatbarColorblindMode>>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform), With<Camera3d>>,
+    billboard_query: Query<(
+        &Statbar<T>,
+        &StatbarBillboard<T>,
+        Option<&StatbarBorder<T>>,
+        Option<&StatbarAutoHide<T>>,
+        &GlobalTransform,
+        &ComputedVisibility,
+    )>,
+    mut quad_query: Query<
+        (
+            &Handle<StatbarBillboardMaterial>,
+            &mut Transform,
+            &mut GlobalTransform,
+            &mut Visibility,
+        ),
+        (
+            With<StatbarBillboardQuad<T>>,
+            Without<StatbarBillboard<T>>,
+            Without<Camera>,
+        ),
+    >,
+) where
+    T: 'static,
+{
+    let mut materials = match materials {
+        Some(materials) => materials,
+        None => return,
+    };
+    let colorblind_mode = colorblind_mode.map(|mode| *mode).unwrap_or_default();
+    let default_camera = default_camera(cameras.iter());
+    billboard_query.for_each(
+        |(bar, billboard, border, auto_hide, global_transform, computed_visibility)| {
+            let quads = match billboard.quads {
+                Some(quads) => quads,
+                None => return,
+            };
+            let camera = match billboard.camera {
+                Some(camera_id) => cameras.get(camera_id).ok(),
+                None => default_camera,
+            };
+            let alpha = auto_hide.map(|auto_hide| auto_hide.alpha()).unwrap_or(1.);
+            let visible = !bar.hide && 0. < alpha && computed_visibility.is_visible_in_hierarchy();
+            let rotation = match camera.filter(|_| visible) {
+                Some((_, _, camera_transform)) => camera_transform.compute_transform().rotation,
+                None => {
+                    for quad in quads {
+                        if let Ok((_, _, _, mut visibility)) = quad_query.get_mut(quad) {
+                            if visibility.is_visible {
+                                visibility.is_visible = false;
+                            }
+                        }
+                    }
+                    return;
+                }
+            };
+            // the bar lies in the camera's xy plane, facing the camera
+            let right = rotation * Vec3::X;
+            let up = rotation * Vec3::Y;
+            let toward_camera = rotation * Vec3::Z;
+            let scale = billboard.scale;
+            let center = global_transform.translation()
+                + billboard.offset
+                + scale * (bar.displacement.x * right + bar.displacement.y * up);
+
+            let (major_axis, minor_axis) = if bar.vertical {
+                (Vec2::Y, Vec2::X)
+            } else {
+                (Vec2::X, Vec2::Y)
+            };
+            let size = statbar_size(bar);
+            let value = bar.value.clamp(0., 1.);
+            let direction = if bar.reverse { -1. } else { 1. };
+            let fill_offset = direction * 0.5 * bar.length * (value - 1.) * major_axis;
+            let layers = [
+                (
+                    border.map(|border| border.color),
+                    statbar_outer_size(bar, border),
+                    statbar_outer_offset(border),
+                ),
+                (
+                    Some(bar.empty_color).filter(|_| value < 1.),
+                    size,
+                    Vec2::ZERO,
+                ),
+                (
+                    Some(bar.color).filter(|_| 0. < value),
+                    value * bar.length * major_axis + bar.thickness * minor_axis,
+                    fill_offset,
+                ),
+            ];
+            for (layer, (quad, (color, layer_size, layer_offset))) in
+                quads.into_iter().zip(layers).enumerate()
+            {
+                let (material, mut transform, mut quad_global_transform, mut visibility) =
+                    match quad_query.get_mut(quad) {
+                        Ok(quad_components) => quad_components,
+                        Err(_) => continue,
+                    };
+                let color = match color {
+                    Some(color) => colorblind_mode.apply(fade_color(color, alpha)),
+                    None => {
+                        if visibility.is_visible {
+                            visibility.is_visible = false;
+                        }
+                        continue;
+                    }
+                };
+                if !visibility.is_visible {
+                    visibility.is_visible = true;
+                }
+                let needs_update = materials.get(material).is_some_and(|material| {
+                    material.color != color || material.depth_test != billboard.depth_test
+                });
+                if needs_update {
+                    if let Some(material) = materials.get_mut(material) {
+                        material.color = color;
+                        material.depth_test = billboard.depth_test;
+                    }
+                }
+                let translation = center
+                    + scale * (layer_offset.x * right + layer_offset.y * up)
+                    + layer as f32 * BILLBOARD_LAYER_STEP * toward_camera;
+                *transform = Transform {
+                    translation,
+                    rotation,
+                    scale: (scale * layer_size).extend(1.),
+                };
+                *quad_global_transform = GlobalTransform::from(*transform);
+            }
+        },
+    );
+}
@@ -0,0 +1,276 @@
+use crate::*;
+use bevy::reflect::FromReflect;
+use serde::Deserialize;
+use serde::Serialize;
+use std::marker::PhantomData;
+
+/// How the colors between the stops of a `ColorRamp` are calculated
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Reflect, FromReflect, Serialize, Deserialize,
+)]
+#[reflect_value(PartialEq, Serialize, Deserialize)]
+pub enum ColorInterpolation {
+    /// no interpolation, the color of the nearest stop at or below the value
+    Step,
+    /// interpolate the linear RGBA components
+    #[default]
+    LinearRgb,
+    /// interpolate hue, saturation and lightness, taking the shortest way around the hue circle
+    Hsl,
+    /// interpolate in the perceptually uniform Oklab color space
+    Oklab,
+}
+
+/// A color at a position on a `ColorRamp`
+#[derive(Clone, Copy, Debug, Reflect, FromReflect)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct ColorStop {
+    /// statbar value where the ramp is this color, from 0.0 to 1.0
+    pub position: f32,
+    pub color: Color,
+}
+
+impl ColorStop {
+    pub fn new(position: f32, color: Color) -> Self {
+        Self { position, color }
+    }
+}
+
+/// A gradient of colors mapped to statbar values.
+/// A ramp without any stops doesn't change any colors.
+#[derive(Clone, Debug, Default, Reflect, FromReflect)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct ColorRamp {
+    /// colors of the ramp, always sorted by position.
+    /// Stops are saved in order, so ramps in hand written scenes must list their stops in order too,
+    /// ramps deserialized with serde are sorted when they're loaded.
+    #[cfg_attr(
+        feature = "serialize",
+        serde(deserialize_with = "deserialize_sorted_stops")
+    )]
+    stops: Vec<ColorStop>,
+    pub interpolation: ColorInterpolation,
+}
+
+impl ColorRamp {
+    pub fn new(stops: impl Into<Vec<ColorStop>>, interpolation: ColorInterpolation) -> Self {
+        let mut ramp = Self {
+            stops: Vec::new(),
+            interpolation,
+        };
+        ramp.set_stops(stops);
+        ramp
+    }
+
+    /// A ramp with colors spaced evenly between 0.0 and 1.0
+    pub fn even(colors: &[Color], interpolation: ColorInterpolation) -> Self {
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+        let stops: Vec<ColorStop> = colors
+            .iter()
+            .enumerate()
+            .map(|(i, &color)| ColorStop::new(i as f32 / last, color))
+            .collect();
+        Self::new(stops, interpolation)
+    }
+
+    /// A ramp from the palette's `low` color at 0.0 to its `high` color at 1.0
+    pub fn from_palette(palette: StatbarPalette, interpolation: ColorInterpolation) -> Self {
+        Self::even(&[palette.low, palette.high], interpolation)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stops.is_empty()
+    }
+
+    /// The stops of the ramp, sorted by position
+    pub fn stops(&self) -> &[ColorStop] {
+        &self.stops
+    }
+
+    /// Replaces the stops of the ramp, stops with the same position keep their order
+    pub fn set_stops(&mut self, stops: impl Into<Vec<ColorStop>>) {
+        self.stops = stops.into();
+        sort_stops(&mut self.stops);
+    }
+
+    /// Adds a stop after any stops with the same position
+    pub fn add_stop(&mut self, stop: ColorStop) {
+        let index = self
+            .stops
+            .partition_point(|other| other.position <= stop.position);
+        self.stops.insert(index, stop);
+    }
+
+    pub fn with_stop(mut self, stop: ColorStop) -> Self {
+        self.add_stop(stop);
+        self
+    }
+
+    /// The color of the ramp at `value`, None if the ramp has no stops
+    pub fn sample(&self, value: f32) -> Option<Color> {
+        let stops = &self.stops;
+        let first = stops.first()?;
+        if value <= first.position {
+            return Some(first.color);
+        }
+        for pair in stops.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            if value < b.position {
+                let t = (value - a.position) / (b.position - a.position);
+                return Some(interpolate(a.color, b.color, t, self.interpolation));
+            }
+        }
+        stops.last().map(|stop| stop.color)
+    }
+}
+
+fn sort_stops(stops: &mut [ColorStop]) {
+    stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+}
+
+#[cfg(feature = "serialize")]
+fn deserialize_sorted_stops<'de, D>(deserializer: D) -> Result<Vec<ColorStop>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let mut stops = Vec::<ColorStop>::deserialize(deserializer)?;
+    sort_stops(&mut stops);
+    Ok(stops)
+}
+
+fn interpolate(a: Color, b: Color, t: f32, interpolation: ColorInterpolation) -> Color {
+    match interpolation {
+        ColorInterpolation::Step => a,
+        ColorInterpolation::LinearRgb => {
+            let c = Vec4::from(a.as_linear_rgba_f32()).lerp(b.as_linear_rgba_f32().into(), t);
+            Color::rgba_linear(c.x, c.y, c.z, c.w)
+        }
+        ColorInterpolation::Hsl => {
+            let [h0, s0, l0, a0] = hsla(a);
+            let [h1, s1, l1, a1] = hsla(b);
+            let mut dh = (h1 - h0) % 360.;
+            if 180. < dh {
+                dh -= 360.;
+            } else if dh < -180. {
+                dh += 360.;
+            }
+            Color::hsla(
+                (h0 + t * dh).rem_euclid(360.),
+                s0 + t * (s1 - s0),
+                l0 + t * (l1 - l0),
+                a0 + t * (a1 - a0),
+            )
+        }
+        ColorInterpolation::Oklab => {
+            let [r0, g0, b0, a0] = a.as_linear_rgba_f32();
+            let [r1, g1, b1, a1] = b.as_linear_rgba_f32();
+            let lab = linear_srgb_to_oklab(Vec3::new(r0, g0, b0))
+                .lerp(linear_srgb_to_oklab(Vec3::new(r1, g1, b1)), t);
+            let rgb = oklab_to_linear_srgb(lab);
+            Color::rgba_linear(rgb.x, rgb.y, rgb.z, a0 + t * (a1 - a0))
+        }
+    }
+}
+
+fn hsla(color: Color) -> [f32; 4] {
+    match color.as_hsla() {
+        Color::Hsla {
+            hue,
+            saturation,
+            lightness,
+            alpha,
+        } => [hue, saturation, lightness, alpha],
+        _ => unreachable!(),
+    }
+}
+
+fn linear_srgb_to_oklab(c: Vec3) -> Vec3 {
+    let l = (0.41222146 * c.x + 0.53633255 * c.y + 0.051445995 * c.z).cbrt();
+    let m = (0.2119035 * c.x + 0.6806995 * c.y + 0.10739696 * c.z).cbrt();
+    let s = (0.08830246 * c.x + 0.28171885 * c.y + 0.6299787 * c.z).cbrt();
+    Vec3::new(
+        0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+        1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+        0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+    )
+}
+
+fn oklab_to_linear_srgb(c: Vec3) -> Vec3 {
+    let l = (c.x + 0.39633778 * c.y + 0.21580376 * c.z).powi(3);
+    let m = (c.x - 0.105561346 * c.y - 0.06385417 * c.z).powi(3);
+    let s = (c.x - 0.08948418 * c.y - 1.2914855 * c.z).powi(3);
+    Vec3::new(
+        4.0767417 * l - 3.3077116 * m + 0.23096994 * s,
+        -1.268438 * l + 2.6097574 * m - 0.34131938 * s,
+        -0.0041960864 * l - 0.7034186 * m + 1.7076147 * s,
+    )
+}
+
+/// Sets the colors of the corresponding Statbar and StatbarBorder from color ramps,
+/// using the value of the Statbar.
+///
+/// The fill color, empty color and border color each have their own ramp.
+/// Ramps without any stops are ignored.
+#[derive(Clone, Debug, Component, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
+pub struct StatbarColorRamp<T>
+where
+    T: 'static,
+{
+    /// sets `Statbar::color`
+    pub fill: ColorRamp,
+    /// sets `Statbar::empty_color`
+    pub empty: ColorRamp,
+    /// sets `StatbarBorder::color`
+    pub border: ColorRamp,
+    /// when several color rules set the same color, the rule with the highest priority is used
+    pub priority: i32,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarColorRamp<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self {
+            fill: Default::default(),
+            empty: Default::default(),
+            border: Default::default(),
+            priority: 0,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> StatbarColorRamp<T>
+where
+    T: 'static,
+{
+    /// A ramp that sets the fill color of the bar
+    pub fn fill(ramp: ColorRamp) -> Self {
+        Self {
+            fill: ramp,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_empty(mut self, ramp: ColorRamp) -> Self {
+        self.empty = ramp;
+        self
+    }
+
+    pub fn with_border(mut self, ramp: ColorRamp) -> Self {
+        self.border = ramp;
+        self
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
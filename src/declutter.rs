@@ -0,0 +1,303 @@
+use crate::extraction::default_camera;
+use crate::extraction::statbar_outer_offset;
+use crate::extraction::statbar_outer_size;
+use crate::*;
+use bevy::math::vec2;
+use bevy::utils::HashMap;
+#[cfg(feature = "serialize")]
+use serde::Deserialize;
+#[cfg(feature = "serialize")]
+use serde::Serialize;
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+/// How overlapping statbars with a `StatbarDeclutter` component are decluttered
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum StatbarDeclutterMode {
+    /// Move bars out of the way of higher priority bars, by at most `max_displacement`.
+    /// Bars that can't be moved to a free space are culled.
+    Nudge,
+    /// Don't draw bars that overlap higher priority bars.
+    Cull,
+}
+
+/// Insert as a resource to configure the declutter pass.
+///
+/// Bars are decluttered on the screen of the active camera with the highest priority,
+/// which is assumed to be an orthographic camera, so they are kept apart however far the camera is zoomed.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct StatbarDeclutterSettings {
+    pub mode: StatbarDeclutterMode,
+    /// furthest distance in logical pixels a bar can be moved from its position
+    pub max_displacement: f32,
+    /// minimum space in logical pixels between decluttered bars
+    pub padding: f32,
+    /// width of the line drawn from a moved bar back to its position
+    /// * 0.0 => no leader lines
+    pub leader_line_width: f32,
+    /// color of the leader lines
+    pub leader_line_color: Color,
+}
+
+impl Default for StatbarDeclutterSettings {
+    fn default() -> Self {
+        Self {
+            mode: StatbarDeclutterMode::Nudge,
+            max_displacement: 32.,
+            padding: 1.,
+            leader_line_width: 1.,
+            leader_line_color: Color::GRAY,
+        }
+    }
+}
+
+/// Insert this component to stop the corresponding Statbar from overlapping
+/// other statbars with a `StatbarDeclutter` component.
+///
+/// When bars overlap, the bars with the higher priority keep their position
+/// and the lower priority bars are moved or culled.
+/// Bars with equal priority are ordered by their entity id.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
+pub struct StatbarDeclutter<T>
+where
+    T: 'static,
+{
+    pub priority: i32,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarDeclutter<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<T> StatbarDeclutter<T>
+where
+    T: 'static,
+{
+    pub fn new(priority: i32) -> Self {
+        Self {
+            priority,
+            phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum DeclutterResult {
+    Moved(Vec2),
+    Culled,
+}
+
+struct DeclutterEntry {
+    entity: Entity,
+    type_id: TypeId,
+    priority: i32,
+    /// world space center of the bar
+    center: Vec3,
+    /// world space half extents of the bar
+    half_extents: Vec2,
+}
+
+#[derive(Default)]
+pub(crate) struct StatbarDeclutterState {
+    entries: Vec<DeclutterEntry>,
+    results: HashMap<(Entity, TypeId), DeclutterResult>,
+}
+
+impl StatbarDeclutterState {
+    pub(crate) fn get<T: 'static>(&self, entity: Entity) -> Option<DeclutterResult> {
+        self.results.get(&(entity, TypeId::of::<T>())).copied()
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn collect_statbar_bounds<T>(
+    mut state: ResMut<StatbarDeclutterState>,
+    query: Query<(
+        Entity,
+        &Statbar<T>,
+        Option<&StatbarBorder<T>>,
+        &StatbarDeclutter<T>,
+        Option<&StatbarAutoHide<T>>,
+        &GlobalTransform,
+        &ComputedVisibility,
+    )>,
+) where
+    T: 'static,
+{
+    query.for_each(
+        |(entity, bar, border, declutter, auto_hide, global_transform, computed_visibility)| {
+            let hidden = auto_hide.is_some_and(|auto_hide| auto_hide.alpha() <= 0.);
+            if bar.hide || hidden || !computed_visibility.is_visible() {
+                return;
+            }
+            let size = statbar_outer_size(bar, border);
+            state.entries.push(DeclutterEntry {
+                entity,
+                type_id: TypeId::of::<T>(),
+                priority: declutter.priority,
+                center: global_transform.translation()
+                    + (bar.displacement + statbar_outer_offset(border)).extend(0.),
+                half_extents: 0.5 * size,
+            });
+        },
+    );
+}
+
+/// Maps world space to logical pixels on the viewport of a camera, with the origin at its center
+struct ScreenProjection {
+    world_to_ndc: Mat4,
+    ndc_to_world: Mat4,
+    half_viewport_size: Vec2,
+}
+
+impl ScreenProjection {
+    fn new(camera: &Camera, camera_transform: &GlobalTransform) -> Option<Self> {
+        let half_viewport_size = 0.5 * camera.logical_viewport_size()?;
+        let world_to_ndc = camera.projection_matrix() * camera_transform.compute_matrix().inverse();
+        Some(Self {
+            world_to_ndc,
+            ndc_to_world: world_to_ndc.inverse(),
+            half_viewport_size,
+        })
+    }
+
+    fn point(&self, point: Vec3) -> Vec2 {
+        self.world_to_ndc.project_point3(point).truncate() * self.half_viewport_size
+    }
+
+    fn extents(&self, extents: Vec2) -> Vec2 {
+        self.world_to_ndc
+            .transform_vector3(extents.extend(0.))
+            .truncate()
+            .abs()
+            * self.half_viewport_size
+    }
+
+    fn offset_to_world(&self, offset: Vec2) -> Vec2 {
+        self.ndc_to_world
+            .transform_vector3((offset / self.half_viewport_size).extend(0.))
+            .truncate()
+    }
+}
+
+fn overlaps(a_center: Vec2, a_half_extents: Vec2, b_center: Vec2, b_half_extents: Vec2) -> bool {
+    let d = (a_center - b_center).abs();
+    let r = a_half_extents + b_half_extents;
+    d.x < r.x && d.y < r.y
+}
+
+pub(crate) fn declutter_statbars(
+    settings: Option<Res<StatbarDeclutterSettings>>,
+    mut state: ResMut<StatbarDeclutterState>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform)>,
+) {
+    // stops bars moved flush against another bar from registering as overlapping
+    const EPSILON: f32 = 1e-3;
+
+    let settings = settings.map(|settings| *settings).unwrap_or_default();
+    let StatbarDeclutterState { entries, results } = &mut *state;
+    results.clear();
+    // without a camera, bars are decluttered in world space
+    let projection = default_camera(cameras.iter())
+        .and_then(|(_, camera, camera_transform)| ScreenProjection::new(camera, camera_transform));
+    let to_screen = |entry: &DeclutterEntry| match &projection {
+        Some(projection) => (
+            projection.point(entry.center),
+            projection.extents(entry.half_extents),
+        ),
+        None => (entry.center.truncate(), entry.half_extents),
+    };
+    entries.sort_by(|a, b| {
+        b.priority
+            .cmp(&a.priority)
+            .then(a.entity.cmp(&b.entity))
+            .then(a.type_id.cmp(&b.type_id))
+    });
+
+    let reach = match settings.mode {
+        StatbarDeclutterMode::Nudge => settings.max_displacement.max(0.),
+        StatbarDeclutterMode::Cull => 0.,
+    };
+    let padding = 0.5 * settings.padding.max(0.) * Vec2::ONE;
+    let max_half_extents = entries
+        .iter()
+        .fold(Vec2::ZERO, |acc, entry| acc.max(to_screen(entry).1))
+        + padding;
+    let cell_size = (2. * max_half_extents).max(Vec2::ONE);
+    let cell = |position: Vec2| (position / cell_size).floor().as_ivec2();
+
+    let mut grid: HashMap<IVec2, Vec<usize>> = HashMap::default();
+    let mut placed: Vec<(Vec2, Vec2)> = Vec::with_capacity(entries.len());
+    let mut neighbours = vec![];
+    let mut candidates = vec![];
+    for entry in entries.drain(..) {
+        let (entry_center, entry_half_extents) = to_screen(&entry);
+        let half_extents = entry_half_extents + padding;
+        let range = reach + half_extents + max_half_extents;
+        let min_cell = cell(entry_center - range);
+        let max_cell = cell(entry_center + range);
+        neighbours.clear();
+        for x in min_cell.x..=max_cell.x {
+            for y in min_cell.y..=max_cell.y {
+                if let Some(indices) = grid.get(&IVec2::new(x, y)) {
+                    neighbours.extend(indices.iter().map(|&index| placed[index]));
+                }
+            }
+        }
+
+        candidates.clear();
+        candidates.push(Vec2::ZERO);
+        if settings.mode == StatbarDeclutterMode::Nudge {
+            for &(center, other_half_extents) in &neighbours {
+                let r = other_half_extents + half_extents + EPSILON;
+                let d = center - entry_center;
+                candidates.extend([
+                    vec2(0., d.y + r.y),
+                    vec2(0., d.y - r.y),
+                    vec2(d.x + r.x, 0.),
+                    vec2(d.x - r.x, 0.),
+                ]);
+            }
+            candidates.retain(|offset| offset.length() <= reach);
+            candidates.sort_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+        }
+
+        let key = (entry.entity, entry.type_id);
+        let free = candidates.iter().copied().find(|&offset| {
+            let center = entry_center + offset;
+            !neighbours.iter().any(|&(other, other_half_extents)| {
+                overlaps(center, half_extents, other, other_half_extents)
+            })
+        });
+        match free {
+            Some(offset) => {
+                let center = entry_center + offset;
+                grid.entry(cell(center)).or_default().push(placed.len());
+                placed.push((center, half_extents));
+                if offset != Vec2::ZERO {
+                    let offset = match &projection {
+                        Some(projection) => projection.offset_to_world(offset),
+                        None => offset,
+                    };
+                    results.insert(key, DeclutterResult::Moved(offset));
+                }
+            }
+            None => {
+                results.insert(key, DeclutterResult::Culled);
+            }
+        }
+    }
+}
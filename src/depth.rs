@@ -0,0 +1,108 @@
+use crate::extraction::base_z;
+use crate::extraction::statbar_depth;
+use crate::extraction::LAYER_STEP;
+use crate::*;
+use bevy::utils::HashMap;
+use std::any::TypeId;
+
+/// Number of layer steps reserved for the sprites of each statbar,
+/// the top sprite of a statbar is drawn four steps above its bottom sprite.
+const LAYERS_PER_BAR: f32 = 5.0;
+
+/// Greatest depth range shared by statbars with the same depth,
+/// the range the sprites of a single statbar cover, so ordering bars never lifts them past
+/// anything more than a few hundredths of a unit above their depth.
+const MAX_TIE_BAND: f32 = LAYERS_PER_BAR * LAYER_STEP;
+
+/// Where the sprites of a statbar are drawn, from the bottom sprite at `z` up in steps of `step`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct StatbarLayers {
+    pub(crate) z: f32,
+    pub(crate) step: f32,
+}
+
+impl StatbarLayers {
+    /// The z depth of the sprite drawn `layer` steps above the bottom of the statbar
+    pub(crate) fn layer(self, layer: f32) -> f32 {
+        self.z + layer * self.step
+    }
+}
+
+struct DepthEntry {
+    entity: Entity,
+    type_id: TypeId,
+    depth: f32,
+}
+
+/// The z depths assigned to every statbar this frame.
+///
+/// Statbars with the same depth are spread over the space up to the next greater depth,
+/// or the space one statbar's sprites cover if that's smaller, in the order of their entity ids, so bars are always drawn in the same order
+/// and bars with a greater depth are always drawn on top.
+#[derive(Default)]
+pub(crate) struct StatbarDepthOrder {
+    entries: Vec<DepthEntry>,
+    layers: HashMap<(Entity, TypeId), StatbarLayers>,
+}
+
+impl StatbarDepthOrder {
+    pub(crate) fn get<T: 'static>(&self, entity: Entity) -> Option<StatbarLayers> {
+        self.layers.get(&(entity, TypeId::of::<T>())).copied()
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn collect_statbar_depths<T>(
+    depth: Option<Res<StatbarDepth>>,
+    mut order: ResMut<StatbarDepthOrder>,
+    query: Query<(Entity, Option<&StatbarDepthOffset<T>>, &GlobalTransform), With<Statbar<T>>>,
+) where
+    T: 'static,
+{
+    let base_z = base_z(depth.as_deref());
+    query.for_each(|(entity, depth_offset, global_transform)| {
+        order.entries.push(DepthEntry {
+            entity,
+            type_id: TypeId::of::<T>(),
+            depth: statbar_depth(base_z, depth_offset, global_transform),
+        });
+    });
+}
+
+pub(crate) fn order_statbar_depths(mut order: ResMut<StatbarDepthOrder>) {
+    let StatbarDepthOrder { entries, layers } = &mut *order;
+    layers.clear();
+    entries.sort_by(|a, b| {
+        a.depth
+            .total_cmp(&b.depth)
+            .then(a.entity.cmp(&b.entity))
+            .then(a.type_id.cmp(&b.type_id))
+    });
+    let mut start = 0;
+    while start < entries.len() {
+        let depth = entries[start].depth;
+        let end = entries[start..]
+            .iter()
+            .position(|entry| entry.depth != depth)
+            .map_or(entries.len(), |count| start + count);
+        let band = entries
+            .get(end)
+            .map_or(MAX_TIE_BAND, |next| (next.depth - depth).min(MAX_TIE_BAND));
+        let spacing = band / (end - start) as f32;
+        // keep quarter steps at least a couple of representable f32 values apart,
+        // with too many bars at the same depth their layers overlap rather than collapse together
+        let min_step = 4. * f32::EPSILON * depth.abs().max(1.);
+        let step = (spacing / LAYERS_PER_BAR).min(LAYER_STEP).max(min_step);
+        for (rank, entry) in entries[start..end].iter().enumerate() {
+            layers.insert(
+                (entry.entity, entry.type_id),
+                StatbarLayers {
+                    z: depth + rank as f32 * spacing,
+                    step,
+                },
+            );
+        }
+        start = end;
+    }
+    entries.clear();
+}
@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+#[cfg(feature = "serialize")]
+use serde::Deserialize;
+#[cfg(feature = "serialize")]
+use serde::Serialize;
+
+/// How the edges of sprite statbars are drawn, insert as a resource to change it for every statbar.
+///
+/// Pixels are measured with the active camera with the highest priority,
+/// which is assumed to be an unrotated orthographic camera.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum StatbarEdgeMode {
+    /// edges are drawn wherever they fall, like any other sprite
+    #[default]
+    Plain,
+    /// the edges of the bar, its border and its fill are rounded to whole screen pixels,
+    /// so bars don't shimmer as they move and stay crisp at pixel art resolutions
+    PixelSnap,
+    /// the edges of the bar, its border and its fill fade out over one screen pixel,
+    /// so they are antialiased even when sprite textures are sampled with the nearest filter.
+    ///
+    /// There's no antialiasing shader, each rectangle is drawn as up to nine sprites with the corners
+    /// and sides cut from a soft edge texture, so smooth bars draw several times as many sprites as plain bars.
+    /// With thousands of bars, draw the fill with a `StatbarMaterial` that antialiases it instead.
+    Smooth,
+}
+
+/// The edge mode with the screen pixel grid of the camera the statbars are drawn for
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct StatbarEdges {
+    mode: StatbarEdgeMode,
+    /// world space width and height of a physical pixel
+    pixel_size: Vec2,
+    /// world space position of a corner of the pixel grid
+    origin: Vec2,
+}
+
+impl StatbarEdges {
+    /// Without a camera to measure pixels with, edges are drawn plainly
+    pub(crate) fn new(
+        mode: StatbarEdgeMode,
+        camera: Option<(&Camera, &GlobalTransform)>,
+    ) -> StatbarEdges {
+        let plain = StatbarEdges {
+            mode: StatbarEdgeMode::Plain,
+            pixel_size: Vec2::ONE,
+            origin: Vec2::ZERO,
+        };
+        if mode == StatbarEdgeMode::Plain {
+            return plain;
+        }
+        let (camera, camera_transform) = match camera {
+            Some(camera) => camera,
+            None => return plain,
+        };
+        let viewport_size = match camera.physical_viewport_size() {
+            Some(viewport_size) => viewport_size.as_vec2(),
+            None => return plain,
+        };
+        // measured along both axes, projections can scale the x and y axes differently
+        let world_to_ndc = camera.projection_matrix() * camera_transform.compute_matrix().inverse();
+        let pixels_per_unit = 0.5
+            * viewport_size
+            * Vec2::new(
+                world_to_ndc.transform_vector3(Vec3::X).length(),
+                world_to_ndc.transform_vector3(Vec3::Y).length(),
+            );
+        if !(0. < pixels_per_unit.min_element() && pixels_per_unit.is_finite()) {
+            return plain;
+        }
+        let pixel_size = pixels_per_unit.recip();
+        StatbarEdges {
+            mode,
+            pixel_size,
+            // the left and bottom edges of the viewport are on the grid
+            origin: camera_transform.translation().truncate() - 0.5 * viewport_size * pixel_size,
+        }
+    }
+
+    /// The rectangle with its edges rounded to whole pixels in `PixelSnap` mode, unchanged otherwise
+    pub(crate) fn snap(&self, center: Vec2, size: Vec2) -> (Vec2, Vec2) {
+        if self.mode != StatbarEdgeMode::PixelSnap {
+            return (center, size);
+        }
+        let round = |point: Vec2| {
+            ((point - self.origin) / self.pixel_size).round() * self.pixel_size + self.origin
+        };
+        let min = round(center - 0.5 * size);
+        let max = round(center + 0.5 * size);
+        (0.5 * (min + max), max - min)
+    }
+
+    /// Distance each side of an edge is faded over in `Smooth` mode
+    pub(crate) fn smoothing(&self) -> Option<Vec2> {
+        match self.mode {
+            StatbarEdgeMode::Smooth => Some(0.5 * self.pixel_size),
+            _ => None,
+        }
+    }
+}
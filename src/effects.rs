@@ -0,0 +1,254 @@
+use crate::*;
+#[cfg(feature = "serialize")]
+use serde::Deserialize;
+#[cfg(feature = "serialize")]
+use serde::Serialize;
+use std::f32::consts::TAU;
+use std::marker::PhantomData;
+
+/// Linearly interpolate between two colors in RGBA space, the same way as `StatbarColorLerp`
+pub(crate) fn mix_colors(a: Color, b: Color, t: f32) -> Color {
+    Vec4::from(a).lerp(b.into(), t.clamp(0., 1.)).into()
+}
+
+/// Briefly flashes the full part of the corresponding Statbar when its value decreases.
+///
+/// Applied when the bar is drawn, on top of the color set by `StatbarColorSwitch` or `StatbarColorLerp`.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
+pub struct StatbarFlash<T>
+where
+    T: 'static,
+{
+    /// color the bar flashes to
+    pub color: Color,
+    /// seconds the flash takes to fade
+    pub duration: f32,
+    /// seconds of the flash remaining
+    remaining: f32,
+    /// NaN before the first check
+    last_value: f32,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarFlash<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(Color::WHITE, 0.15)
+    }
+}
+
+impl<T> StatbarFlash<T>
+where
+    T: 'static,
+{
+    pub fn new(color: Color, duration: f32) -> Self {
+        Self {
+            color,
+            duration,
+            remaining: 0.,
+            last_value: f32::NAN,
+            phantom: PhantomData,
+        }
+    }
+
+    pub(crate) fn apply(&self, color: Color) -> Color {
+        if 0. < self.remaining && 0. < self.duration {
+            mix_colors(color, self.color, self.remaining / self.duration)
+        } else {
+            color
+        }
+    }
+}
+
+/// Pulses the full part of the corresponding Statbar while its value is below a critical threshold.
+///
+/// Applied when the bar is drawn, on top of the color set by `StatbarColorSwitch` or `StatbarColorLerp`.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
+pub struct StatbarPulse<T>
+where
+    T: 'static,
+{
+    /// the bar pulses while its value is less than or equal to this threshold
+    pub threshold: f32,
+    /// color the bar pulses to
+    pub color: Color,
+    /// pulses per second
+    pub frequency: f32,
+    /// false => bar smoothly fades between its color and the pulse color,
+    /// true => bar blinks between its color and the pulse color
+    pub blink: bool,
+    /// phase of the pulse, 0.0 to 1.0
+    phase: f32,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarPulse<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(0.25, Color::WHITE, 2.)
+    }
+}
+
+impl<T> StatbarPulse<T>
+where
+    T: 'static,
+{
+    pub fn new(threshold: f32, color: Color, frequency: f32) -> Self {
+        Self {
+            threshold,
+            color,
+            frequency,
+            blink: false,
+            phase: 0.,
+            phantom: PhantomData,
+        }
+    }
+
+    pub(crate) fn apply(&self, color: Color, value: f32) -> Color {
+        if self.threshold < value {
+            return color;
+        }
+        let t = if self.blink {
+            if self.phase < 0.5 {
+                0.
+            } else {
+                1.
+            }
+        } else {
+            0.5 - 0.5 * (TAU * self.phase).cos()
+        };
+        mix_colors(color, self.color, t)
+    }
+}
+
+/// Shakes the corresponding Statbar when its value decreases by a large amount.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
+pub struct StatbarShake<T>
+where
+    T: 'static,
+{
+    /// the bar shakes when its value decreases by at least this much in a single frame
+    pub min_decrease: f32,
+    /// furthest distance the bar is moved by the shake
+    pub magnitude: f32,
+    /// seconds the shake lasts for
+    pub duration: f32,
+    /// seconds of the shake remaining
+    remaining: f32,
+    /// NaN before the first check
+    last_value: f32,
+    /// current displacement of the bar due to the shake
+    offset: Vec2,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarShake<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(0.2, 3., 0.25)
+    }
+}
+
+impl<T> StatbarShake<T>
+where
+    T: 'static,
+{
+    pub fn new(min_decrease: f32, magnitude: f32, duration: f32) -> Self {
+        Self {
+            min_decrease,
+            magnitude,
+            duration,
+            remaining: 0.,
+            last_value: f32::NAN,
+            offset: Vec2::ZERO,
+            phantom: PhantomData,
+        }
+    }
+
+    pub(crate) fn offset(&self) -> Vec2 {
+        self.offset
+    }
+}
+
+pub(crate) fn update_statbar_flashes<T>(
+    time: Res<Time>,
+    mut flash_query: Query<(&Statbar<T>, &mut StatbarFlash<T>)>,
+) where
+    T: 'static,
+{
+    let delta = time.delta_seconds();
+    flash_query.for_each_mut(|(bar, mut flash)| {
+        if bar.value < flash.last_value {
+            flash.remaining = flash.duration;
+        } else if 0. < flash.remaining {
+            flash.remaining = (flash.remaining - delta).max(0.);
+        }
+        if flash.last_value != bar.value {
+            flash.last_value = bar.value;
+        }
+    });
+}
+
+pub(crate) fn update_statbar_pulses<T>(
+    time: Res<Time>,
+    mut pulse_query: Query<(&Statbar<T>, &mut StatbarPulse<T>)>,
+) where
+    T: 'static,
+{
+    let delta = time.delta_seconds();
+    pulse_query.for_each_mut(|(bar, mut pulse)| {
+        if bar.value <= pulse.threshold {
+            pulse.phase = (pulse.phase + delta * pulse.frequency).fract();
+        } else if pulse.phase != 0. {
+            pulse.phase = 0.;
+        }
+    });
+}
+
+pub(crate) fn update_statbar_shakes<T>(
+    time: Res<Time>,
+    mut shake_query: Query<(&Statbar<T>, &mut StatbarShake<T>)>,
+) where
+    T: 'static,
+{
+    let delta = time.delta_seconds();
+    let t = time.seconds_since_startup() as f32;
+    shake_query.for_each_mut(|(bar, mut shake)| {
+        if shake.min_decrease <= shake.last_value - bar.value {
+            shake.remaining = shake.duration;
+        } else if 0. < shake.remaining {
+            shake.remaining = (shake.remaining - delta).max(0.);
+        }
+        if shake.last_value != bar.value {
+            shake.last_value = bar.value;
+        }
+        if 0. < shake.remaining && 0. < shake.duration {
+            // cheap deterministic noise, no need for a random number generator
+            let strength = shake.magnitude * shake.remaining / shake.duration;
+            shake.offset = strength * Vec2::new((73. * t).sin(), (91. * t).cos());
+        } else if shake.offset != Vec2::ZERO {
+            shake.offset = Vec2::ZERO;
+        }
+    });
+}
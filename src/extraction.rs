@@ -1,3 +1,15 @@
+use crate::auto_hide::fade_color;
+use crate::declutter::DeclutterResult;
+use crate::declutter::StatbarDeclutterState;
+use crate::depth::StatbarDepthOrder;
+use crate::depth::StatbarLayers;
+use crate::edges::StatbarEdges;
+use crate::fill_quad::StatbarCustomFill;
+use crate::lod::camera_zoom;
+use crate::lod::statbar_lod_level;
+use crate::offscreen::clamp_to_viewport;
+use crate::shadow::soft_rect_quads;
+use crate::shadow::SOFT_EDGE_HANDLE;
 use crate::*;
 use bevy::math::vec2;
 use bevy::math::Vec3A;
@@ -6,28 +18,366 @@ use bevy::render::texture::DEFAULT_IMAGE_HANDLE;
 use bevy::render::Extract;
 use bevy::sprite::ExtractedSprite;
 use bevy::sprite::ExtractedSprites;
+use bevy::sprite::Rect;
 use copyless::VecHelper;
 
 /// The z depth the stat bar sprites are drawn with.
 const DEFAULT_Z_DEPTH: f32 = 990.0;
 
+/// Greatest depth between the leader line, shadow, glow, border, back, front, pattern, inner border and icon sprites of a single statbar.
+/// Statbars sharing a depth with many other bars use smaller steps, see `StatbarDepthOrder`.
+pub(crate) const LAYER_STEP: f32 = 1.0 / 256.0;
+
+/// The depth of a statbar before it's ordered against statbars with the same depth
+pub(crate) fn statbar_depth<V>(
+    base_z: f32,
+    depth_offset: Option<&StatbarDepthOffset<V>>,
+    global_transform: &GlobalTransform,
+) -> f32
+where
+    V: 'static,
+{
+    match depth_offset {
+        Some(depth_offset) if depth_offset.relative => {
+            global_transform.translation().z + depth_offset.offset
+        }
+        Some(depth_offset) => base_z + depth_offset.offset,
+        None => base_z,
+    }
+}
+
+/// The z depths of the sprites of a statbar,
+/// anything drawn one unit above the bottom sprite is drawn on top of the statbar.
+pub(crate) fn statbar_layers<V>(
+    id: Entity,
+    depth_order: Option<&StatbarDepthOrder>,
+    base_z: f32,
+    depth_offset: Option<&StatbarDepthOffset<V>>,
+    global_transform: &GlobalTransform,
+) -> StatbarLayers
+where
+    V: 'static,
+{
+    depth_order
+        .and_then(|depth_order| depth_order.get::<V>(id))
+        .unwrap_or_else(|| StatbarLayers {
+            z: statbar_depth(base_z, depth_offset, global_transform),
+            step: LAYER_STEP,
+        })
+}
+
+/// The base z depth of statbars without a `StatbarDepthOffset` component
+pub(crate) fn base_z(depth: Option<&StatbarDepth>) -> f32 {
+    depth.map(|depth| depth.0).unwrap_or(DEFAULT_Z_DEPTH)
+}
+
+/// The active camera with the highest priority
+pub(crate) fn default_camera<'a>(
+    cameras: impl Iterator<Item = (Entity, &'a Camera, &'a GlobalTransform)>,
+) -> Option<(Entity, &'a Camera, &'a GlobalTransform)> {
+    cameras
+        .filter(|(_, camera, _)| camera.is_active)
+        .max_by_key(|(_, camera, _)| camera.priority)
+}
+
+/// The size of a statbar, not including its border
+pub(crate) fn statbar_size<V>(bar: &Statbar<V>) -> Vec2 {
+    if bar.vertical {
+        vec2(bar.thickness, bar.length)
+    } else {
+        vec2(bar.length, bar.thickness)
+    }
+}
+
+/// The size of a statbar including its border
+pub(crate) fn statbar_outer_size<V>(bar: &Statbar<V>, border: Option<&StatbarBorder<V>>) -> Vec2 {
+    let size = statbar_size(bar);
+    border
+        .map(|border| {
+            let (low, high) = border.outer_extents();
+            size + low + high
+        })
+        .unwrap_or(size)
+}
+
+/// Offset from the center of a statbar to the center of the statbar including its border,
+/// non-zero when the border is thicker on one side than the other
+pub(crate) fn statbar_outer_offset<V>(border: Option<&StatbarBorder<V>>) -> Vec2 {
+    border
+        .map(|border| {
+            let (low, high) = border.outer_extents();
+            0.5 * (high - low)
+        })
+        .unwrap_or(Vec2::ZERO)
+}
+
+/// A rectangle drawn as part of a border, positioned relative to the center of its statbar
+struct BorderQuad {
+    offset: Vec2,
+    size: Vec2,
+    color: Color,
+}
+
+/// Draws the four sides of a frame around the rectangle from `min` to `max`,
+/// sides with no thickness are left out
+fn push_frame(
+    draw: &mut impl FnMut(BorderQuad),
+    min: Vec2,
+    max: Vec2,
+    low: Vec2,
+    high: Vec2,
+    [left_color, right_color, bottom_color, top_color]: [Color; 4],
+) {
+    let outer_min = min - low;
+    let outer_max = max + high;
+    let sides = [
+        (
+            vec2(outer_min.x, outer_min.y),
+            vec2(min.x, outer_max.y),
+            left_color,
+        ),
+        (
+            vec2(max.x, outer_min.y),
+            vec2(outer_max.x, outer_max.y),
+            right_color,
+        ),
+        (vec2(min.x, outer_min.y), vec2(max.x, min.y), bottom_color),
+        (vec2(min.x, max.y), vec2(max.x, outer_max.y), top_color),
+    ];
+    for (side_min, side_max, color) in sides {
+        let size = side_max - side_min;
+        if 0. < size.x && 0. < size.y {
+            draw(BorderQuad {
+                offset: 0.5 * (side_min + side_max),
+                size,
+                color,
+            });
+        }
+    }
+}
+
+/// Draws the quads of a border around a statbar of `size` with `draw`
+fn draw_border_quads<V>(border: &StatbarBorder<V>, size: Vec2, mut draw: impl FnMut(BorderQuad)) {
+    let half_size = 0.5 * size;
+    let low = vec2(border.left, border.bottom);
+    let high = vec2(border.right, border.top);
+    let side_colors = border.side_colors();
+    match border.kind {
+        StatbarBorderKind::Outer => {
+            if side_colors.iter().all(|&color| color == border.color) {
+                // a single quad drawn behind the bar
+                draw(BorderQuad {
+                    offset: 0.5 * (high - low),
+                    size: size + low + high,
+                    color: border.color,
+                });
+            } else {
+                push_frame(&mut draw, -half_size, half_size, low, high, side_colors);
+            }
+        }
+        StatbarBorderKind::Inner => {
+            push_frame(
+                &mut draw,
+                -half_size + low,
+                half_size - high,
+                low,
+                high,
+                side_colors,
+            );
+        }
+        StatbarBorderKind::Double { gap, gap_color } => {
+            let (outer_low, outer_high) = border.outer_extents();
+            let gap_low = outer_low - 2. * low;
+            let gap_high = outer_high - 2. * high;
+            push_frame(&mut draw, -half_size, half_size, low, high, side_colors);
+            if gap_color.a() > 0. && 0. < gap {
+                push_frame(
+                    &mut draw,
+                    -half_size - low,
+                    half_size + high,
+                    gap_low,
+                    gap_high,
+                    [gap_color; 4],
+                );
+            }
+            push_frame(
+                &mut draw,
+                -half_size - low - gap_low,
+                half_size + high + gap_high,
+                low,
+                high,
+                side_colors,
+            );
+        }
+    }
+}
+
+/// Draws a rectangle whose edges fade out over `2 * blur`, centered on its edges.
+/// Drawn as up to nine sprites cut from the soft edge image, see `soft_rect_quads`.
+fn push_soft_rect(
+    extracted_sprites: &mut ExtractedSprites,
+    id: Entity,
+    center: Vec2,
+    size: Vec2,
+    blur: Vec2,
+    z: f32,
+    color: Color,
+) {
+    for quad in soft_rect_quads(size, blur) {
+        let quad_transform = Transform::from_translation((center + quad.offset).extend(z));
+        extracted_sprites.sprites.alloc().init(ExtractedSprite {
+            entity: id,
+            transform: quad_transform.into(),
+            color,
+            rect: quad.rect,
+            custom_size: Some(quad.size),
+            image_handle_id: match quad.rect {
+                Some(_) => SOFT_EDGE_HANDLE.id,
+                None => DEFAULT_IMAGE_HANDLE.into(),
+            },
+            flip_x: false,
+            flip_y: false,
+            anchor: Default::default(),
+        });
+    }
+}
+
+/// Draws a solid rectangle, snapped to pixels or with smoothed edges depending on the edge mode
+fn push_rect(
+    extracted_sprites: &mut ExtractedSprites,
+    id: Entity,
+    edges: &StatbarEdges,
+    center: Vec2,
+    size: Vec2,
+    z: f32,
+    color: Color,
+) {
+    let (center, size) = edges.snap(center, size);
+    if size.x <= 0. || size.y <= 0. {
+        return;
+    }
+    if let Some(blur) = edges.smoothing() {
+        push_soft_rect(extracted_sprites, id, center, size, blur, z, color);
+        return;
+    }
+    extracted_sprites.sprites.alloc().init(ExtractedSprite {
+        entity: id,
+        transform: Transform::from_translation(center.extend(z)).into(),
+        color,
+        rect: None,
+        custom_size: Some(size),
+        image_handle_id: DEFAULT_IMAGE_HANDLE.into(),
+        flip_x: false,
+        flip_y: false,
+        anchor: Default::default(),
+    });
+}
+
 pub(crate) fn extract_stat_bars<V>(
     extraction: Extract<(
         Option<Res<StatbarDepth>>,
-        Query<(
-            Entity,
-            &Statbar<V>,
-            Option<&StatbarBorder<V>>,
-            &GlobalTransform,
-            &ComputedVisibility,
-        )>,
+        Option<Res<StatbarDepthOrder>>,
+        Option<Res<StatbarDeclutterState>>,
+        Option<Res<StatbarDeclutterSettings>>,
+        Option<Res<StatbarLodSettings>>,
+        Option<Res<StatbarIcon<V>>>,
+        Option<Res<StatbarColorblindMode>>,
+        Option<Res<StatbarEdgeMode>>,
+        Query<
+            (
+                Entity,
+                &Statbar<V>,
+                Option<&StatbarBorder<V>>,
+                Option<&StatbarDepthOffset<V>>,
+                Option<&StatbarEdgeClamp<V>>,
+                Option<&StatbarAutoHide<V>>,
+                Option<&StatbarLod<V>>,
+                Option<&StatbarIcon<V>>,
+                Option<&StatbarPattern<V>>,
+                (
+                    Option<&StatbarShadow<V>>,
+                    Option<&StatbarGlow<V>>,
+                    Option<&StatbarCustomFill<V>>,
+                ),
+                (
+                    Option<&StatbarFlash<V>>,
+                    Option<&StatbarPulse<V>>,
+                    Option<&StatbarShake<V>>,
+                ),
+                &GlobalTransform,
+                &ComputedVisibility,
+            ),
+            (Without<StatbarNode<V>>, Without<StatbarBillboard<V>>),
+        >,
+        Query<(Entity, &Camera, &GlobalTransform)>,
     )>,
     mut extracted_sprites: ResMut<ExtractedSprites>,
-) {
+) where
+    V: 'static,
+{
     let mut transform = GlobalTransform::default();
-    let (depth, query) = &*extraction;
-    for (id, bar, border, global_transform, computed_visibility) in query.iter() {
-        if bar.hide || !computed_visibility.is_visible() {
+    let (
+        depth,
+        depth_order,
+        declutter_state,
+        declutter_settings,
+        lod_settings,
+        default_icon,
+        colorblind_mode,
+        edge_mode,
+        query,
+        cameras,
+    ) = &*extraction;
+    let colorblind_mode = colorblind_mode
+        .as_ref()
+        .map(|mode| **mode)
+        .unwrap_or_default();
+    let declutter_settings = declutter_settings
+        .as_ref()
+        .map(|settings| **settings)
+        .unwrap_or_default();
+    let base_z = base_z(depth.as_deref());
+    let default_camera = default_camera(cameras.iter());
+    let zoom = default_camera
+        .and_then(|(_, camera, camera_transform)| camera_zoom(camera, camera_transform));
+    let edges = StatbarEdges::new(
+        edge_mode.as_ref().map(|mode| **mode).unwrap_or_default(),
+        default_camera.map(|(_, camera, camera_transform)| (camera, camera_transform)),
+    );
+    for (
+        id,
+        bar,
+        border,
+        depth_offset,
+        edge_clamp,
+        auto_hide,
+        lod,
+        icon,
+        pattern,
+        (shadow, glow, custom_fill),
+        (flash, pulse, shake),
+        global_transform,
+        computed_visibility,
+    ) in query.iter()
+    {
+        // off-screen entities are culled, but edge clamped bars are still drawn
+        let visible = if edge_clamp.is_some() {
+            computed_visibility.is_visible_in_hierarchy()
+        } else {
+            computed_visibility.is_visible()
+        };
+        let alpha = auto_hide.map(|auto_hide| auto_hide.alpha()).unwrap_or(1.);
+        if bar.hide || !visible || alpha <= 0. {
+            continue;
+        }
+        let paint = |color: Color| colorblind_mode.apply(fade_color(color, alpha));
+        let lod_level = statbar_lod_level(
+            lod.map(|lod| &lod.settings).or(lod_settings.as_deref()),
+            default_camera.map(|(_, _, camera_transform)| camera_transform),
+            zoom,
+            global_transform.translation().truncate() + bar.displacement,
+        );
+        if lod_level == StatbarLodLevel::Culled {
             continue;
         }
         let (major_axis, minor_axis) = if bar.vertical {
@@ -40,26 +390,168 @@ pub(crate) fn extract_stat_bars<V>(
         let length = bar.length;
         let thickness = bar.thickness;
         *transform.translation_mut() = global_transform.translation_vec3a();
-        let z = depth
-            .as_ref()
-            .map(|depth| depth.0)
-            .unwrap_or(DEFAULT_Z_DEPTH);
+        let layers = statbar_layers(
+            id,
+            depth_order.as_deref(),
+            base_z,
+            depth_offset,
+            global_transform,
+        );
+        let z = layers.z;
         transform.translation_mut().z = z;
-        transform.translation_mut().x += bar.displacement.x;
-        transform.translation_mut().y += bar.displacement.y;
-        let size = length * major_axis + thickness * minor_axis;
-        if let Some(border) = border {
-            let border_size = vec2(
-                size.x + border.left + border.right,
-                size.y + border.bottom + border.top,
+        let displacement = bar.displacement + shake.map(|shake| shake.offset()).unwrap_or_default();
+        transform.translation_mut().x += displacement.x;
+        transform.translation_mut().y += displacement.y;
+        let size = statbar_size(bar);
+        match declutter_state
+            .as_ref()
+            .and_then(|declutter_state| declutter_state.get::<V>(id))
+        {
+            Some(DeclutterResult::Culled) => continue,
+            Some(DeclutterResult::Moved(offset)) => {
+                if 0. < declutter_settings.leader_line_width {
+                    let leader_transform = Transform::from_translation(
+                        (transform.translation().truncate() + 0.5 * offset).extend(z),
+                    )
+                    .with_rotation(Quat::from_rotation_z(offset.y.atan2(offset.x)));
+                    extracted_sprites.sprites.alloc().init(ExtractedSprite {
+                        entity: id,
+                        transform: leader_transform.into(),
+                        color: paint(declutter_settings.leader_line_color),
+                        rect: None,
+                        custom_size: Some(vec2(
+                            offset.length(),
+                            declutter_settings.leader_line_width,
+                        )),
+                        image_handle_id: DEFAULT_IMAGE_HANDLE.into(),
+                        flip_x: false,
+                        flip_y: false,
+                        anchor: Default::default(),
+                    });
+                }
+                transform.translation_mut().x += offset.x;
+                transform.translation_mut().y += offset.y;
+            }
+            None => {}
+        }
+        if let Some(edge_clamp) = edge_clamp {
+            let camera = match edge_clamp.camera {
+                Some(camera_id) => cameras.get(camera_id).ok(),
+                None => default_camera,
+            };
+            let half_extents = 0.5 * statbar_outer_size(bar, border);
+            let clamped = camera.and_then(|(_, camera, camera_transform)| {
+                clamp_to_viewport(
+                    camera,
+                    camera_transform,
+                    transform.translation(),
+                    half_extents,
+                    edge_clamp.margin,
+                )
+            });
+            if let Some((position, direction)) = clamped {
+                transform.translation_mut().x = position.x;
+                transform.translation_mut().y = position.y;
+                if 0. < edge_clamp.arrow_size {
+                    // draw a chevron, two arms meeting at a tip pointing toward the entity
+                    let thickness = 0.25 * edge_clamp.arrow_size;
+                    let tip = position
+                        + direction * (half_extents.dot(direction.abs()) + edge_clamp.arrow_size);
+                    let angle = direction.y.atan2(direction.x);
+                    for arm_angle in [
+                        angle + 0.75 * std::f32::consts::PI,
+                        angle - 0.75 * std::f32::consts::PI,
+                    ] {
+                        let arm = Vec2::from_angle(arm_angle);
+                        // the arms overlap by their thickness at the tip so there's no notch
+                        let arm_center = tip + arm * 0.5 * (edge_clamp.arrow_size - thickness);
+                        let arm_transform = Transform::from_translation(arm_center.extend(z))
+                            .with_rotation(Quat::from_rotation_z(arm_angle));
+                        extracted_sprites.sprites.alloc().init(ExtractedSprite {
+                            entity: id,
+                            transform: arm_transform.into(),
+                            color: paint(edge_clamp.arrow_color),
+                            rect: None,
+                            custom_size: Some(Vec2::new(edge_clamp.arrow_size, thickness)),
+                            image_handle_id: DEFAULT_IMAGE_HANDLE.into(),
+                            flip_x: false,
+                            flip_y: false,
+                            anchor: Default::default(),
+                        });
+                    }
+                }
+            }
+        }
+        if lod_level == StatbarLodLevel::Full {
+            let outer_size = statbar_outer_size(bar, border);
+            let outer_center = transform.translation().truncate() + statbar_outer_offset(border);
+            let glow = glow.map(|glow| {
+                let mut color = glow.color;
+                color.set_a(color.a() * glow.intensity(value));
+                // the glow is fully opaque at the edges of the bar and fades out over its radius
+                (
+                    color,
+                    outer_size + glow.radius,
+                    0.5 * glow.radius,
+                    Vec2::ZERO,
+                    0.5,
+                )
+            });
+            let shadow =
+                shadow.map(|shadow| (shadow.color, outer_size, shadow.blur, shadow.offset, 0.25));
+            for (color, soft_size, blur, offset, layer) in shadow.into_iter().chain(glow) {
+                if color.a() <= 0. {
+                    continue;
+                }
+                push_soft_rect(
+                    &mut extracted_sprites,
+                    id,
+                    outer_center + offset,
+                    soft_size,
+                    Vec2::splat(blur),
+                    layers.layer(layer),
+                    paint(color),
+                );
+            }
+        }
+
+        if let Some(border) = border.filter(|_| lod_level == StatbarLodLevel::Full) {
+            // inner borders are drawn over the bar, below the icon
+            let border_z = if border.kind == StatbarBorderKind::Inner {
+                layers.layer(3.75)
+            } else {
+                layers.layer(1.0)
+            };
+            let center = transform.translation().truncate();
+            draw_border_quads(border, size, |quad| {
+                push_rect(
+                    &mut extracted_sprites,
+                    id,
+                    &edges,
+                    center + quad.offset,
+                    quad.size,
+                    border_z,
+                    paint(quad.color),
+                );
+            });
+        }
+
+        // draw icon
+        if let Some(icon) = icon
+            .or(default_icon.as_deref())
+            .filter(|_| lod_level < StatbarLodLevel::SingleQuad)
+        {
+            let (icon_position, icon_size) = icon.placement(bar);
+            let icon_transform = Transform::from_translation(
+                (transform.translation().truncate() + icon_position).extend(layers.layer(4.0)),
             );
             extracted_sprites.sprites.alloc().init(ExtractedSprite {
                 entity: id,
-                transform,
-                color: border.color,
+                transform: icon_transform.into(),
+                color: paint(icon.color),
                 rect: None,
-                custom_size: Some(border_size),
-                image_handle_id: DEFAULT_IMAGE_HANDLE.into(),
+                custom_size: Some(icon_size),
+                image_handle_id: icon.image.id,
                 flip_x: false,
                 flip_y: false,
                 anchor: Default::default(),
@@ -67,40 +559,70 @@ pub(crate) fn extract_stat_bars<V>(
         }
 
         // draw bar back
-        if value < 1.0 {
-            transform.translation_mut().z = z + 1.0;
-            extracted_sprites.sprites.alloc().init(ExtractedSprite {
-                entity: id,
-                transform,
-                color: bar.empty_color,
-                rect: None,
-                custom_size: Some(size),
-                image_handle_id: DEFAULT_IMAGE_HANDLE.into(),
-                flip_x: false,
-                flip_y: false,
-                anchor: Default::default(),
-            });
+        if value < 1.0 && lod_level < StatbarLodLevel::SingleQuad {
+            push_rect(
+                &mut extracted_sprites,
+                id,
+                &edges,
+                transform.translation().truncate(),
+                size,
+                layers.layer(2.0),
+                paint(bar.empty_color),
+            );
         }
 
         // draw bar
         if 0.0 < value {
+            let mut color = bar.color;
+            if let Some(pulse) = pulse {
+                color = pulse.apply(color, value);
+            }
+            if let Some(flash) = flash {
+                color = flash.apply(color);
+            }
             let value = value.clamp(0., 1.);
             let bar_size = value * length * major_axis + thickness * minor_axis;
             let direction = if bar.reverse { -1. } else { 1. };
             *transform.translation_mut() +=
                 Vec3A::from(direction * 0.5 * length * (value - 1.) * major_axis.extend(0.));
-            transform.translation_mut().z = z + 2.0;
-            extracted_sprites.sprites.alloc().init(ExtractedSprite {
-                entity: id,
-                transform,
-                color: bar.color,
-                rect: None,
-                custom_size: Some(bar_size),
-                image_handle_id: DEFAULT_IMAGE_HANDLE.into(),
-                flip_x: false,
-                flip_y: false,
-                anchor: Default::default(),
-            });
+            // animated fills are drawn by their own quad
+            if custom_fill.is_none() {
+                push_rect(
+                    &mut extracted_sprites,
+                    id,
+                    &edges,
+                    transform.translation().truncate(),
+                    bar_size,
+                    layers.layer(3.0),
+                    paint(color),
+                );
+            }
+
+            // draw pattern over the bar, tiled from the texture's repeating sampler
+            if let Some(pattern) = pattern.filter(|pattern| {
+                value <= pattern.threshold
+                    && 0. < pattern.scale
+                    && lod_level < StatbarLodLevel::SingleQuad
+            }) {
+                let (pattern_center, pattern_size) =
+                    edges.snap(transform.translation().truncate(), bar_size);
+                let pattern_transform =
+                    Transform::from_translation(pattern_center.extend(layers.layer(3.5)));
+                extracted_sprites.sprites.alloc().init(ExtractedSprite {
+                    entity: id,
+                    transform: pattern_transform.into(),
+                    color: paint(pattern.color),
+                    rect: Some(Rect {
+                        min: Vec2::ZERO,
+                        max: pattern_size / pattern.scale,
+                    }),
+                    custom_size: Some(pattern_size),
+                    image_handle_id: pattern.kind.image().id,
+                    flip_x: false,
+                    flip_y: false,
+                    anchor: Default::default(),
+                });
+            }
         }
     }
 }
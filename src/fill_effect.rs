@@ -0,0 +1,278 @@
+use crate::fill_quad::despawn_orphaned_fill_quads;
+use crate::fill_quad::fill_quad;
+use crate::fill_quad::init_statbar_fill_mesh;
+use crate::fill_quad::place_fill_quad;
+use crate::fill_quad::spawn_fill_quad;
+use crate::fill_quad::FillQuadFilter;
+use crate::fill_quad::FillQuadItem;
+use crate::fill_quad::FillQuadResources;
+use crate::fill_quad::StatbarCustomFill;
+use crate::fill_quad::StatbarFillMesh;
+use crate::fill_quad::StatbarFillQuad;
+use crate::*;
+use bevy::asset::load_internal_asset;
+use bevy::reflect::TypeUuid;
+use bevy::render::render_resource::AsBindGroup;
+use bevy::render::render_resource::ShaderRef;
+use bevy::sprite::Material2d;
+use bevy::sprite::Material2dPlugin;
+use serde::Deserialize;
+use serde::Serialize;
+use std::marker::PhantomData;
+
+const FILL_EFFECT_SHADER_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 9466433493212431972);
+
+/// Adds the mesh, material and shader needed to draw animated fills with `StatbarFillEffect`.
+pub struct StatbarFillEffectPlugin;
+
+impl Plugin for StatbarFillEffectPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            FILL_EFFECT_SHADER_HANDLE,
+            "fill_effect.wgsl",
+            Shader::from_wgsl
+        );
+        app.add_plugin(Material2dPlugin::<StatbarFillMaterial>::default());
+        init_statbar_fill_mesh(app);
+    }
+}
+
+/// An animation drawn on the full part of a statbar
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect_value(PartialEq, Serialize, Deserialize)]
+pub enum StatbarFillEffectKind {
+    /// diagonal stripes in the highlight color, scrolling along the bar
+    ScrollingStripes,
+    /// a band of the highlight color sweeping along the bar
+    Shimmer,
+    /// the edge of the fill ripples like the surface of a liquid,
+    /// best suited to vertical bars
+    Wave,
+    /// the fill breaks up into drifting noise near its edge
+    Dissolve,
+}
+
+impl StatbarFillEffectKind {
+    fn index(self) -> u32 {
+        match self {
+            Self::ScrollingStripes => 0,
+            Self::Shimmer => 1,
+            Self::Wave => 2,
+            Self::Dissolve => 3,
+        }
+    }
+}
+
+/// Draws the full part of the corresponding Statbar with an animated shader
+/// instead of a flat colored sprite.
+///
+/// Requires `StatbarFillEffectPlugin`. The fill follows displacement, shakes, decluttering and edge clamping,
+/// and is drawn with the bar's color, including its flashes and pulses.
+/// Not drawn for statbars with a `StatbarNode` or `StatbarBillboard`.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
+pub struct StatbarFillEffect<T>
+where
+    T: 'static,
+{
+    pub kind: StatbarFillEffectKind,
+    /// color of the stripes, shimmer, wave crest or dissolving edge
+    pub highlight: Color,
+    /// speed of the animation in world units per second
+    pub speed: f32,
+    /// size of the stripes, shimmer, waves or noise in world units
+    pub scale: f32,
+    /// quad drawing the fill
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    quad: Option<Entity>,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarFillEffect<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(StatbarFillEffectKind::Shimmer)
+    }
+}
+
+impl<T> StatbarFillEffect<T>
+where
+    T: 'static,
+{
+    /// A translucent white effect
+    pub fn new(kind: StatbarFillEffectKind) -> Self {
+        Self {
+            kind,
+            highlight: Color::rgba(1., 1., 1., 0.4),
+            speed: 20.,
+            scale: 8.,
+            quad: None,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn with_highlight(mut self, highlight: Color) -> Self {
+        self.highlight = highlight;
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+}
+
+pub use uniform::StatbarFillUniform;
+
+// the size checks generated by `ShaderType` are never called
+#[allow(dead_code)]
+mod uniform {
+    use bevy::prelude::*;
+    use bevy::render::render_resource::ShaderType;
+
+    /// The parameters of a `StatbarFillMaterial`, laid out as the fill effect shader expects
+    #[derive(Clone, Copy, Debug, Default, ShaderType)]
+    pub struct StatbarFillUniform {
+        pub color: Vec4,
+        pub highlight: Vec4,
+        pub size: Vec2,
+        pub value: f32,
+        pub time: f32,
+        pub speed: f32,
+        pub scale: f32,
+        pub effect: u32,
+        /// 1 => vertical, 2 => reverse
+        pub flags: u32,
+    }
+}
+
+/// The material of the quads drawn by `StatbarFillEffect`
+#[derive(AsBindGroup, TypeUuid, Clone, Debug, Default)]
+#[uuid = "5b0c7d2e-8f43-4a61-b1d9-3e72a6c4f815"]
+pub struct StatbarFillMaterial {
+    #[uniform(0)]
+    pub uniform: StatbarFillUniform,
+}
+
+impl Material2d for StatbarFillMaterial {
+    fn fragment_shader() -> ShaderRef {
+        FILL_EFFECT_SHADER_HANDLE.typed().into()
+    }
+}
+
+pub(crate) fn spawn_statbar_fill_effects<T>(
+    mut commands: Commands,
+    mesh: Option<Res<StatbarFillMesh>>,
+    materials: Option<ResMut<Assets<StatbarFillMaterial>>>,
+    mut effect_query: Query<(Entity, &mut StatbarFillEffect<T>), Changed<StatbarFillEffect<T>>>,
+) where
+    T: 'static,
+{
+    let (mesh, mut materials) = match (mesh, materials) {
+        (Some(mesh), Some(materials)) => (mesh, materials),
+        _ => return,
+    };
+    effect_query.for_each_mut(|(bar, mut effect)| {
+        if effect.quad.is_some() {
+            return;
+        }
+        let material = materials.add(StatbarFillMaterial::default());
+        effect.quad = Some(spawn_fill_quad::<T, StatbarFillEffect<T>, _>(
+            &mut commands,
+            &mesh,
+            bar,
+            material,
+        ));
+    });
+}
+
+pub(crate) fn despawn_orphaned_statbar_fill_effects<T>(
+    commands: Commands,
+    removed: RemovedComponents<StatbarFillEffect<T>>,
+    quad_query: Query<(Entity, &StatbarFillQuad<StatbarFillEffect<T>>)>,
+    effect_query: Query<&StatbarFillEffect<T>>,
+    custom_fill_query: Query<(), With<StatbarCustomFill<T>>>,
+) where
+    T: 'static,
+{
+    despawn_orphaned_fill_quads::<T, _>(
+        commands,
+        removed,
+        quad_query,
+        effect_query,
+        custom_fill_query,
+        |effect| effect.quad,
+    );
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_statbar_fill_effects<T>(
+    resources: FillQuadResources,
+    materials: Option<ResMut<Assets<StatbarFillMaterial>>>,
+    effect_query: Query<(FillQuadItem<T>, &StatbarFillEffect<T>), FillQuadFilter<T>>,
+    mut quad_query: Query<
+        (
+            &Handle<StatbarFillMaterial>,
+            &mut Transform,
+            &mut GlobalTransform,
+            &mut Visibility,
+        ),
+        (
+            With<StatbarFillQuad<StatbarFillEffect<T>>>,
+            Without<StatbarFillEffect<T>>,
+            Without<Camera>,
+        ),
+    >,
+) where
+    T: 'static,
+{
+    let mut materials = match materials {
+        Some(materials) => materials,
+        None => return,
+    };
+    let context = resources.context();
+    effect_query.for_each(|(item, effect)| {
+        let (material, mut transform, mut global_transform, mut visibility) =
+            match effect.quad.map(|quad| quad_query.get_mut(quad)) {
+                Some(Ok(quad_components)) => quad_components,
+                _ => return,
+            };
+        let quad = fill_quad(item, &context).filter(|(_, params)| 0. < params.value);
+        if let Some((_, params)) = &quad {
+            if let Some(material) = materials.get_mut(material) {
+                let highlight = context.colorblind_mode.apply(effect.highlight);
+                material.uniform = StatbarFillUniform {
+                    color: Vec4::from(params.color.as_linear_rgba_f32()),
+                    highlight: Vec4::from(highlight.as_linear_rgba_f32()),
+                    size: params.size,
+                    value: params.value,
+                    time: params.time,
+                    speed: effect.speed,
+                    scale: effect.scale,
+                    effect: effect.kind.index(),
+                    flags: params.vertical as u32 | (params.reverse as u32) << 1,
+                };
+            }
+        }
+        place_fill_quad(
+            quad.map(|(placement, _)| placement),
+            &mut transform,
+            &mut global_transform,
+            &mut visibility,
+        );
+    });
+}
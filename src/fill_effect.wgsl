@@ -0,0 +1,94 @@
+struct StatbarFillMaterial {
+    color: vec4<f32>,
+    highlight: vec4<f32>,
+    size: vec2<f32>,
+    value: f32,
+    time: f32,
+    speed: f32,
+    scale: f32,
+    effect: u32,
+    flags: u32,
+};
+
+@group(1) @binding(0)
+var<uniform> material: StatbarFillMaterial;
+
+let EFFECT_STRIPES: u32 = 0u;
+let EFFECT_SHIMMER: u32 = 1u;
+let EFFECT_WAVE: u32 = 2u;
+let EFFECT_DISSOLVE: u32 = 3u;
+
+let FLAG_VERTICAL: u32 = 1u;
+let FLAG_REVERSE: u32 = 2u;
+
+let TAU: f32 = 6.28318530718;
+
+fn hash(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, vec2<f32>(127.1, 311.7))) * 43758.5453);
+}
+
+fn value_noise(p: vec2<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    let bottom = mix(hash(i), hash(i + vec2<f32>(1.0, 0.0)), u.x);
+    let top = mix(hash(i + vec2<f32>(0.0, 1.0)), hash(i + vec2<f32>(1.0, 1.0)), u.x);
+    return mix(bottom, top, u.y);
+}
+
+@fragment
+fn fragment(
+    #import bevy_sprite::mesh2d_vertex_output
+) -> @location(0) vec4<f32> {
+    // position on the bar in world units, from its bottom left corner
+    let position = vec2<f32>(uv.x, 1.0 - uv.y) * material.size;
+    var along = position.x;
+    var across = position.y;
+    var length = material.size.x;
+    if ((material.flags & FLAG_VERTICAL) != 0u) {
+        along = position.y;
+        across = position.x;
+        length = material.size.y;
+    }
+    if ((material.flags & FLAG_REVERSE) != 0u) {
+        along = length - along;
+    }
+
+    let time = material.time * material.speed;
+    let scale = max(material.scale, 0.0001);
+    var edge = material.value * length;
+    var color = material.color.rgb;
+    var highlight = 0.0;
+
+    if (material.effect == EFFECT_STRIPES) {
+        // diagonal stripes scrolling toward the fill edge
+        let stripe = fract((along + across - time) / scale);
+        highlight = smoothstep(0.45, 0.5, stripe) - smoothstep(0.95, 1.0, stripe);
+    } else if (material.effect == EFFECT_SHIMMER) {
+        // a bright band sweeping along the fill, pausing off the end between sweeps
+        let sweep = fract(time / (length + 4.0 * scale)) * (length + 4.0 * scale) - 2.0 * scale;
+        highlight = 1.0 - smoothstep(0.0, scale, abs(along - sweep + 0.5 * (across - 0.5 * scale)));
+    } else if (material.effect == EFFECT_WAVE) {
+        // the fill edge ripples like the surface of a liquid, flat when full or empty
+        let calm = step(material.value, 0.0) + step(1.0, material.value);
+        let amplitude = 0.15 * scale * (1.0 - min(calm, 1.0));
+        edge = edge + amplitude * (sin(TAU * across / scale + time) + 0.5 * sin(TAU * across / (0.7 * scale) - 1.3 * time));
+        highlight = smoothstep(edge - 0.25 * scale, edge, along);
+    } else if (material.effect == EFFECT_DISSOLVE) {
+        // the fill breaks up into drifting noise over the last `scale` before its edge
+        let noise = value_noise(position / (0.25 * scale) + vec2<f32>(0.0, time));
+        let dissolve = clamp((along - (edge - scale)) / scale, 0.0, 1.0);
+        if (material.value < 1.0 && noise < dissolve) {
+            discard;
+        }
+        highlight = smoothstep(0.6, 1.0, dissolve) * step(material.value, 0.999);
+    }
+
+    // antialiased fill edge
+    let coverage = clamp((edge - along) / max(fwidth(along), 0.0001) + 0.5, 0.0, 1.0);
+    if (coverage <= 0.0) {
+        discard;
+    }
+    color = mix(color, material.highlight.rgb, clamp(highlight, 0.0, 1.0) * material.highlight.a);
+    return vec4<f32>(color, material.color.a * coverage);
+}
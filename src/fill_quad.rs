@@ -0,0 +1,319 @@
+use crate::auto_hide::fade_color;
+use crate::declutter::StatbarDeclutterState;
+use crate::depth::StatbarDepthOrder;
+use crate::extraction::base_z;
+use crate::extraction::default_camera;
+use crate::extraction::statbar_layers;
+use crate::extraction::statbar_outer_size;
+use crate::extraction::statbar_size;
+use crate::lod::camera_zoom;
+use crate::lod::statbar_lod_level;
+use crate::offscreen::clamp_to_viewport;
+use crate::slider::statbar_center;
+use crate::*;
+use bevy::ecs::system::SystemParam;
+use bevy::sprite::Material2d;
+use bevy::sprite::MaterialMesh2dBundle;
+use bevy::sprite::Mesh2dHandle;
+use std::marker::PhantomData;
+
+/// The state of a statbar, for the shader drawing its fill
+#[derive(Clone, Copy, Debug)]
+pub struct StatbarFillParams {
+    /// fill color, including flashes, pulses, fading and the `StatbarColorblindMode`
+    pub color: Color,
+    /// color of the empty part of the bar
+    pub empty_color: Color,
+    /// length and thickness of the bar along the x and y axes, in world units
+    pub size: Vec2,
+    /// value of the bar, from 0.0 to 1.0
+    pub value: f32,
+    /// seconds since startup, wrapped every hour to keep its precision
+    pub time: f32,
+    pub vertical: bool,
+    pub reverse: bool,
+}
+
+/// Unit quad shared by the quads drawing statbar fills
+pub(crate) struct StatbarFillMesh(pub(crate) Mesh2dHandle);
+
+pub(crate) fn init_statbar_fill_mesh(app: &mut App) {
+    if app.world.contains_resource::<StatbarFillMesh>() {
+        return;
+    }
+    let quad = app
+        .world
+        .resource_mut::<Assets<Mesh>>()
+        .add(Mesh::from(shape::Quad::new(Vec2::ONE)));
+    app.insert_resource(StatbarFillMesh(quad.into()));
+}
+
+/// Marks a statbar whose fill is drawn by a shader on its own quad, instead of a sprite
+#[derive(Component)]
+pub(crate) struct StatbarCustomFill<T>
+where
+    T: 'static,
+{
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarCustomFill<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// Marks a quad drawing the fill of the statbar on the entity `bar`,
+/// for the fill component `C` on that statbar
+#[derive(Component)]
+pub(crate) struct StatbarFillQuad<C>
+where
+    C: Component,
+{
+    pub(crate) bar: Entity,
+    phantom: PhantomData<fn() -> C>,
+}
+
+/// Spawns an invisible quad to draw the fill of `bar` with `material`
+pub(crate) fn spawn_fill_quad<T, C, M>(
+    commands: &mut Commands,
+    mesh: &StatbarFillMesh,
+    bar: Entity,
+    material: Handle<M>,
+) -> Entity
+where
+    T: 'static,
+    C: Component,
+    M: Material2d,
+{
+    commands
+        .entity(bar)
+        .insert(StatbarCustomFill::<T>::default());
+    commands
+        .spawn_bundle(MaterialMesh2dBundle {
+            mesh: mesh.0.clone(),
+            material,
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(StatbarFillQuad::<C> {
+            bar,
+            phantom: PhantomData,
+        })
+        .id()
+}
+
+/// The components that place and color the quad drawing a statbar's fill
+pub(crate) type FillQuadItem<'a, T> = (
+    Entity,
+    &'a Statbar<T>,
+    (
+        Option<&'a StatbarBorder<T>>,
+        Option<&'a StatbarDepthOffset<T>>,
+        Option<&'a StatbarAutoHide<T>>,
+        Option<&'a StatbarEdgeClamp<T>>,
+        Option<&'a StatbarLod<T>>,
+    ),
+    (
+        Option<&'a StatbarFlash<T>>,
+        Option<&'a StatbarPulse<T>>,
+        Option<&'a StatbarShake<T>>,
+    ),
+    &'a GlobalTransform,
+    &'a ComputedVisibility,
+);
+
+/// Statbars drawn as sprites, UI nodes and billboards draw their fills themselves
+pub(crate) type FillQuadFilter<T> = (Without<StatbarNode<T>>, Without<StatbarBillboard<T>>);
+
+/// The resources and cameras shared by every fill quad.
+///
+/// Queries for the quads need a `Without<Camera>` filter,
+/// as the cameras' global transforms are read here.
+#[derive(SystemParam)]
+pub(crate) struct FillQuadResources<'w, 's> {
+    time: Res<'w, Time>,
+    depth: Option<Res<'w, StatbarDepth>>,
+    depth_order: Option<Res<'w, StatbarDepthOrder>>,
+    declutter_state: Option<Res<'w, StatbarDeclutterState>>,
+    colorblind_mode: Option<Res<'w, StatbarColorblindMode>>,
+    lod_settings: Option<Res<'w, StatbarLodSettings>>,
+    cameras: Query<'w, 's, (Entity, &'static Camera, &'static GlobalTransform)>,
+}
+
+impl<'w, 's> FillQuadResources<'w, 's> {
+    pub(crate) fn context(&self) -> FillQuadContext<'_> {
+        let default_camera = default_camera(self.cameras.iter());
+        FillQuadContext {
+            base_z: base_z(self.depth.as_deref()),
+            depth_order: self.depth_order.as_deref(),
+            declutter_state: self.declutter_state.as_deref(),
+            lod_settings: self.lod_settings.as_deref(),
+            colorblind_mode: self.colorblind_mode.as_deref().copied().unwrap_or_default(),
+            time: (self.time.seconds_since_startup() % 3600.) as f32,
+            default_camera,
+            zoom: default_camera
+                .and_then(|(_, camera, camera_transform)| camera_zoom(camera, camera_transform)),
+            cameras: &self.cameras,
+        }
+    }
+}
+
+/// The state shared by every fill quad in a frame
+pub(crate) struct FillQuadContext<'a> {
+    pub(crate) base_z: f32,
+    pub(crate) depth_order: Option<&'a StatbarDepthOrder>,
+    pub(crate) declutter_state: Option<&'a StatbarDeclutterState>,
+    pub(crate) lod_settings: Option<&'a StatbarLodSettings>,
+    pub(crate) colorblind_mode: StatbarColorblindMode,
+    pub(crate) time: f32,
+    pub(crate) default_camera: Option<(Entity, &'a Camera, &'a GlobalTransform)>,
+    pub(crate) zoom: Option<f32>,
+    pub(crate) cameras: &'a Query<'a, 'a, (Entity, &'static Camera, &'static GlobalTransform)>,
+}
+
+/// The transform of the quad drawing a statbar's fill and the state of the bar for its shader,
+/// None if the bar isn't drawn
+pub(crate) fn fill_quad<T>(
+    (
+        id,
+        bar,
+        (border, depth_offset, auto_hide, edge_clamp, lod),
+        (flash, pulse, shake),
+        global_transform,
+        computed_visibility,
+    ): FillQuadItem<T>,
+    context: &FillQuadContext,
+) -> Option<(Transform, StatbarFillParams)>
+where
+    T: 'static,
+{
+    // off-screen entities are culled, but edge clamped bars are still drawn, like the bar's sprites
+    let visible = if edge_clamp.is_some() {
+        computed_visibility.is_visible_in_hierarchy()
+    } else {
+        computed_visibility.is_visible()
+    };
+    let alpha = auto_hide.map(|auto_hide| auto_hide.alpha()).unwrap_or(1.);
+    if bar.hide || alpha <= 0. || !visible {
+        return None;
+    }
+    let lod_level = statbar_lod_level(
+        lod.map(|lod| &lod.settings).or(context.lod_settings),
+        context
+            .default_camera
+            .map(|(_, _, camera_transform)| camera_transform),
+        context.zoom,
+        global_transform.translation().truncate() + bar.displacement,
+    );
+    if lod_level == StatbarLodLevel::Culled {
+        return None;
+    }
+    let mut center = statbar_center(id, bar, global_transform, context.declutter_state)?
+        + shake.map(|shake| shake.offset()).unwrap_or_default();
+    if let Some(edge_clamp) = edge_clamp {
+        let camera = match edge_clamp.camera {
+            Some(camera_id) => context.cameras.get(camera_id).ok(),
+            None => context.default_camera,
+        };
+        let clamped = camera.and_then(|(_, camera, camera_transform)| {
+            clamp_to_viewport(
+                camera,
+                camera_transform,
+                center.extend(global_transform.translation().z),
+                0.5 * statbar_outer_size(bar, border),
+                edge_clamp.margin,
+            )
+        });
+        if let Some((position, _)) = clamped {
+            center = position;
+        }
+    }
+    // the quad covers the whole bar, the shader draws the fill up to the bar's value
+    let size = statbar_size(bar);
+    let z = statbar_layers(
+        id,
+        context.depth_order,
+        context.base_z,
+        depth_offset,
+        global_transform,
+    )
+    .layer(3.0);
+    let transform = Transform::from_translation(center.extend(z)).with_scale(size.extend(1.));
+
+    let value = bar.value.clamp(0., 1.);
+    let mut color = bar.color;
+    if let Some(pulse) = pulse {
+        color = pulse.apply(color, value);
+    }
+    if let Some(flash) = flash {
+        color = flash.apply(color);
+    }
+    let paint = |color: Color| context.colorblind_mode.apply(fade_color(color, alpha));
+    let params = StatbarFillParams {
+        color: paint(color),
+        empty_color: paint(bar.empty_color),
+        size,
+        value,
+        time: context.time,
+        vertical: bar.vertical,
+        reverse: bar.reverse,
+    };
+    Some((transform, params))
+}
+
+/// Places a fill quad, or hides it when its statbar isn't drawn
+pub(crate) fn place_fill_quad(
+    placement: Option<Transform>,
+    transform: &mut Transform,
+    global_transform: &mut GlobalTransform,
+    visibility: &mut Visibility,
+) {
+    match placement {
+        Some(placement) => {
+            if !visibility.is_visible {
+                visibility.is_visible = true;
+            }
+            *transform = placement;
+            *global_transform = GlobalTransform::from(placement);
+        }
+        None => {
+            if visibility.is_visible {
+                visibility.is_visible = false;
+            }
+        }
+    }
+}
+
+/// Despawns fill quads whose statbar no longer draws its fill with them,
+/// and lets statbars whose custom fill was removed draw their fill sprite again
+pub(crate) fn despawn_orphaned_fill_quads<T, C>(
+    mut commands: Commands,
+    removed: RemovedComponents<C>,
+    quad_query: Query<(Entity, &StatbarFillQuad<C>)>,
+    fill_query: Query<&C>,
+    custom_fill_query: Query<(), With<StatbarCustomFill<T>>>,
+    quad_of: impl Fn(&C) -> Option<Entity>,
+) where
+    T: 'static,
+    C: Component,
+{
+    despawn_orphans(
+        &mut commands,
+        &quad_query,
+        &fill_query,
+        |quad| quad.bar,
+        |fill, quad_entity| quad_of(fill) == Some(quad_entity),
+    );
+    for bar in removed.iter() {
+        // despawned statbars don't match the query
+        if custom_fill_query.contains(bar) {
+            commands.entity(bar).remove::<StatbarCustomFill<T>>();
+        }
+    }
+}
@@ -0,0 +1,388 @@
+use crate::declutter::StatbarDeclutterState;
+use crate::depth::StatbarDepthOrder;
+use crate::extraction::base_z;
+use crate::extraction::default_camera;
+use crate::extraction::statbar_layers;
+use crate::extraction::statbar_outer_size;
+use crate::label::titled_text;
+use crate::offscreen::clamp_to_viewport;
+use crate::slider::cursor_world_position;
+use crate::slider::statbar_center;
+use crate::slider::statbar_contains;
+use crate::text::StatbarText;
+use crate::text::StatbarTextSource;
+use crate::*;
+use bevy::ecs::entity::EntityMap;
+use bevy::ecs::entity::MapEntities;
+use bevy::ecs::entity::MapEntitiesError;
+use bevy::ecs::reflect::ReflectMapEntities;
+use std::marker::PhantomData;
+
+/// Detects when the mouse cursor is over the corresponding Statbar or its border.
+///
+/// While the cursor is over the bar, the entity has a `StatbarHovered<T>` marker component.
+/// Statbars with a `StatbarTooltip` are also checked without this component.
+/// The bar is hit tested where it's drawn, following shakes, decluttering and edge clamping.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component, MapEntities)]
+pub struct StatbarHover<T>
+where
+    T: 'static,
+{
+    /// camera used to find the cursor's position in the world,
+    /// None => the active camera with the highest priority
+    pub camera: Option<Entity>,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarHover<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self {
+            camera: None,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> MapEntities for StatbarHover<T>
+where
+    T: 'static,
+{
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        map_camera_entity(&mut self.camera, entity_map);
+        Ok(())
+    }
+}
+
+impl<T> StatbarHover<T>
+where
+    T: 'static,
+{
+    pub fn with_camera(camera: Entity) -> Self {
+        Self {
+            camera: Some(camera),
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// Marks statbars that the mouse cursor is over.
+/// Use `Added<StatbarHovered<T>>` and `RemovedComponents<StatbarHovered<T>>`
+/// to find when the cursor enters and leaves a bar.
+#[derive(Component)]
+pub struct StatbarHovered<T>
+where
+    T: 'static,
+{
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarHovered<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// Shows a text tooltip beside the mouse cursor while it is over the corresponding Statbar.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarTooltip<T>
+where
+    T: 'static,
+{
+    /// displayed before the formatted value, like "Hit Points"
+    pub title: String,
+    /// not saved in scenes, `StatbarLabelFormat::Custom` closures can't be serialized
+    #[reflect(ignore)]
+    pub format: StatbarLabelFormat,
+    pub font: Handle<Font>,
+    pub font_size: f32,
+    pub color: Color,
+    /// displacement of the top left corner of the tooltip from the cursor
+    pub offset: Vec2,
+    /// current and maximum value of the observed stat
+    #[reflect(ignore)]
+    current_max: Option<(f32, f32)>,
+    #[reflect(ignore)]
+    text_entity: Option<Entity>,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarTooltip<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl<T> StatbarTooltip<T>
+where
+    T: 'static,
+{
+    /// A white tooltip showing the current and maximum value of the observed stat
+    pub fn new(font: Handle<Font>) -> Self {
+        Self {
+            title: String::new(),
+            format: StatbarLabelFormat::CurrentMax,
+            font,
+            font_size: 14.,
+            color: Color::WHITE,
+            offset: Vec2::new(12., -12.),
+            current_max: None,
+            text_entity: None,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn with_format(mut self, format: StatbarLabelFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_font_size(mut self, font_size: f32) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+impl<T> StatbarTextSource for StatbarTooltip<T>
+where
+    T: 'static,
+{
+    fn text_entity(&self) -> Option<Entity> {
+        self.text_entity
+    }
+
+    fn set_text_entity(&mut self, text_entity: Entity) {
+        self.text_entity = Some(text_entity);
+    }
+
+    fn current_max(&self) -> Option<(f32, f32)> {
+        self.current_max
+    }
+
+    fn set_current_max(&mut self, current_max: Option<(f32, f32)>) {
+        self.current_max = current_max;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_statbar_hover<T>(
+    mut commands: Commands,
+    windows: Option<Res<Windows>>,
+    declutter_state: Option<Res<StatbarDeclutterState>>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform)>,
+    hover_query: Query<
+        (
+            Entity,
+            &Statbar<T>,
+            Option<&StatbarBorder<T>>,
+            Option<&StatbarAutoHide<T>>,
+            (Option<&StatbarEdgeClamp<T>>, Option<&StatbarShake<T>>),
+            Option<&StatbarHover<T>>,
+            Option<&StatbarHovered<T>>,
+            &GlobalTransform,
+            &ComputedVisibility,
+        ),
+        Or<(With<StatbarHover<T>>, With<StatbarTooltip<T>>)>,
+    >,
+) where
+    T: 'static,
+{
+    let default_camera = default_camera(cameras.iter());
+    let cursor_seen_by = |camera: Option<(Entity, &Camera, &GlobalTransform)>| {
+        let windows = windows.as_deref()?;
+        camera.and_then(|(_, camera, camera_transform)| {
+            cursor_world_position(windows, camera, camera_transform)
+        })
+    };
+    let default_cursor = cursor_seen_by(default_camera);
+    hover_query.for_each(
+        |(
+            id,
+            bar,
+            border,
+            auto_hide,
+            (edge_clamp, shake),
+            hover,
+            hovered,
+            global_transform,
+            computed_visibility,
+        )| {
+            let cursor = match hover.and_then(|hover| hover.camera) {
+                Some(camera_id) => cursor_seen_by(cameras.get(camera_id).ok()),
+                None => default_cursor,
+            };
+            // edge clamped bars are drawn while their entity is off-screen
+            let visible = if edge_clamp.is_some() {
+                computed_visibility.is_visible_in_hierarchy()
+            } else {
+                computed_visibility.is_visible()
+            };
+            let alpha = auto_hide.map(|auto_hide| auto_hide.alpha()).unwrap_or(1.);
+            let center = statbar_center(id, bar, global_transform, declutter_state.as_deref())
+                .filter(|_| !bar.hide && 0. < alpha && visible)
+                .map(|center| {
+                    let center = center + shake.map(|shake| shake.offset()).unwrap_or_default();
+                    let camera = match edge_clamp.and_then(|edge_clamp| edge_clamp.camera) {
+                        Some(camera_id) => cameras.get(camera_id).ok(),
+                        None => default_camera,
+                    };
+                    edge_clamp
+                        .zip(camera)
+                        .and_then(|(edge_clamp, (_, camera, camera_transform))| {
+                            clamp_to_viewport(
+                                camera,
+                                camera_transform,
+                                center.extend(global_transform.translation().z),
+                                0.5 * statbar_outer_size(bar, border),
+                                edge_clamp.margin,
+                            )
+                        })
+                        .map_or(center, |(position, _)| position)
+                });
+            let is_hovered = cursor
+                .zip(center)
+                .is_some_and(|(cursor, center)| statbar_contains(bar, border, center, cursor));
+            match (is_hovered, hovered.is_some()) {
+                (true, false) => {
+                    commands.entity(id).insert(StatbarHovered::<T>::default());
+                }
+                (false, true) => {
+                    commands.entity(id).remove::<StatbarHovered<T>>();
+                }
+                _ => {}
+            }
+        },
+    );
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_statbar_tooltips<T>(
+    depth: Option<Res<StatbarDepth>>,
+    depth_order: Option<Res<StatbarDepthOrder>>,
+    windows: Option<Res<Windows>>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform)>,
+    tooltip_query: Query<(
+        Entity,
+        &Statbar<T>,
+        &StatbarTooltip<T>,
+        ChangeTrackers<StatbarTooltip<T>>,
+        Option<&StatbarHover<T>>,
+        Option<&StatbarHovered<T>>,
+        Option<&StatbarDepthOffset<T>>,
+        &GlobalTransform,
+    )>,
+    mut text_query: Query<
+        (
+            &mut StatbarText<StatbarTooltip<T>>,
+            &mut Text,
+            &mut Transform,
+            &mut GlobalTransform,
+            &mut Visibility,
+        ),
+        (Without<StatbarTooltip<T>>, Without<Camera>),
+    >,
+) where
+    T: 'static,
+{
+    let base_z = base_z(depth.as_deref());
+    let default_camera = default_camera(cameras.iter());
+    tooltip_query.for_each(
+        |(id, bar, tooltip, tooltip_tracker, hover, hovered, depth_offset, global_transform)| {
+            let text_entity = match tooltip.text_entity {
+                Some(text_entity) => text_entity,
+                None => return,
+            };
+            let (
+                mut tooltip_text,
+                mut text,
+                mut transform,
+                mut text_global_transform,
+                mut visibility,
+            ) = match text_query.get_mut(text_entity) {
+                Ok(text_components) => text_components,
+                Err(_) => return,
+            };
+            let camera = match hover.and_then(|hover| hover.camera) {
+                Some(camera_id) => cameras.get(camera_id).ok(),
+                None => default_camera,
+            };
+            let cursor = hovered.and(windows.as_deref()).and_then(|windows| {
+                camera.and_then(|(_, camera, camera_transform)| {
+                    cursor_world_position(windows, camera, camera_transform)
+                })
+            });
+            let cursor = match cursor {
+                Some(cursor) => cursor,
+                None => {
+                    if visibility.is_visible {
+                        visibility.is_visible = false;
+                    }
+                    return;
+                }
+            };
+            if !visibility.is_visible {
+                visibility.is_visible = true;
+            }
+
+            // drawn above every statbar with the same depth
+            let z = statbar_layers(
+                id,
+                depth_order.as_deref(),
+                base_z,
+                depth_offset,
+                global_transform,
+            )
+            .z + 2.;
+            *transform = Transform::from_translation((cursor + tooltip.offset).extend(z));
+            *text_global_transform = GlobalTransform::from(*transform);
+
+            let displayed = Some((bar.value, tooltip.current_max));
+            let style_changed = text.sections.first().is_none_or(|section| {
+                section.style.font != tooltip.font
+                    || section.style.font_size != tooltip.font_size
+                    || section.style.color != tooltip.color
+            });
+            if tooltip_text.displayed != displayed || tooltip_tracker.is_changed() || style_changed
+            {
+                let value = titled_text(
+                    &tooltip.title,
+                    tooltip.format.format(bar.value, tooltip.current_max),
+                );
+                let style = TextStyle {
+                    font: tooltip.font.clone(),
+                    font_size: tooltip.font_size,
+                    color: tooltip.color,
+                };
+                *text = Text::from_section(value, style).with_alignment(TextAlignment {
+                    vertical: VerticalAlign::Top,
+                    horizontal: HorizontalAlign::Left,
+                });
+                tooltip_text.displayed = displayed;
+            }
+        },
+    );
+}
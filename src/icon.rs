@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use std::marker::PhantomData;
+
+/// Draws an image, like a heart or a mana drop, at one end of the corresponding Statbar.
+///
+/// Insert as a component to add an icon to an individual statbar,
+/// or insert as a resource to add an icon to every statbar with the marker type `T`.
+/// An icon component overrides the resource.
+#[derive(Clone, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarIcon<T>
+where
+    T: 'static,
+{
+    pub image: Handle<Image>,
+    /// tint of the image
+    pub color: Color,
+    /// size of the icon relative to the thickness of the bar
+    pub scale: f32,
+    /// false => icon is drawn at the end of the bar where it starts filling from,
+    /// true => icon is drawn at the end of the bar that is filled last
+    ///
+    /// For example, for a horizontal bar that isn't reversed, false draws the icon on the left.
+    pub at_full_end: bool,
+    /// false => icon sits inside the bar,
+    /// true => icon is centered on the end of the bar and overlaps the border
+    pub overlap_border: bool,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarIcon<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl<T> StatbarIcon<T>
+where
+    T: 'static,
+{
+    /// An icon the same size as the thickness of the bar,
+    /// overlapping the border at the end of the bar where it starts filling from.
+    pub fn new(image: Handle<Image>) -> Self {
+        Self {
+            image,
+            color: Color::WHITE,
+            scale: 1.,
+            at_full_end: false,
+            overlap_border: true,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the position of the center of the icon relative to the center of the bar,
+    /// and the size of the icon.
+    pub(crate) fn placement(&self, bar: &crate::Statbar<T>) -> (Vec2, Vec2) {
+        let size = self.scale * bar.thickness * Vec2::ONE;
+        let major_axis = if bar.vertical { Vec2::Y } else { Vec2::X };
+        // the direction from the center of the bar to the end it starts filling from
+        let start = if bar.reverse { major_axis } else { -major_axis };
+        let direction = if self.at_full_end { -start } else { start };
+        let distance = if self.overlap_border {
+            0.5 * bar.length
+        } else {
+            0.5 * (bar.length - size.x)
+        };
+        (distance * direction, size)
+    }
+}
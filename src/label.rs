@@ -0,0 +1,381 @@
+use crate::auto_hide::fade_color;
+use crate::declutter::StatbarDeclutterState;
+use crate::depth::StatbarDepthOrder;
+use crate::extraction::base_z;
+use crate::extraction::default_camera;
+use crate::extraction::statbar_layers;
+use crate::extraction::statbar_outer_size;
+use crate::lod::camera_zoom;
+use crate::lod::statbar_lod_level;
+use crate::offscreen::clamp_to_viewport;
+use crate::slider::statbar_center;
+use crate::text::StatbarText;
+use crate::text::StatbarTextSource;
+use crate::*;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Space between a statbar and a label placed beside, above or below it
+const LABEL_GAP: f32 = 2.0;
+
+/// What a `StatbarLabel` displays after its title
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub enum StatbarLabelFormat {
+    /// Nothing, just the title
+    TitleOnly,
+    /// The value of the statbar as a percentage, "75%"
+    Percentage,
+    /// The current and maximum value of the observed stat, "15/20".
+    ///
+    /// Requires the observed component or resource to implement
+    /// `StatbarObservable::get_statbar_current_max`, otherwise displays a percentage.
+    CurrentMax,
+    /// Formatted by a user supplied closure.
+    /// Its arguments are the value of the statbar and, if available, the current and maximum value of
+    /// the observed stat.
+    Custom(Arc<dyn Fn(f32, Option<(f32, f32)>) -> String + Send + Sync>),
+}
+
+impl fmt::Debug for StatbarLabelFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::TitleOnly => write!(f, "TitleOnly"),
+            Self::Percentage => write!(f, "Percentage"),
+            Self::CurrentMax => write!(f, "CurrentMax"),
+            Self::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl StatbarLabelFormat {
+    pub(crate) fn format(&self, value: f32, current_max: Option<(f32, f32)>) -> String {
+        let percentage = || format!("{:.0}%", 100. * value.clamp(0., 1.));
+        match self {
+            Self::TitleOnly => String::new(),
+            Self::Percentage => percentage(),
+            Self::CurrentMax => current_max
+                .map(|(current, max)| format!("{:.0}/{:.0}", current, max))
+                .unwrap_or_else(percentage),
+            Self::Custom(format) => format(value, current_max),
+        }
+    }
+}
+
+/// Where a `StatbarLabel` is drawn, relative to its statbar
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect_value(PartialEq, Serialize, Deserialize)]
+pub enum StatbarLabelPlacement {
+    /// centered on the bar
+    Center,
+    Above,
+    Below,
+    Left,
+    Right,
+}
+
+/// Adds a text label to the corresponding Statbar.
+///
+/// The text is only updated when the value of the statbar changes.
+/// The label follows its bar when it's shaken, decluttered or clamped to the edge of the screen,
+/// and is hidden along with it.
+///
+/// Bevy has no default font, so a label needs a font handle to draw anything.
+/// `Default` is only for loading labels from scenes, set the font afterwards or create labels with `new`.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarLabel<T>
+where
+    T: 'static,
+{
+    /// displayed before the formatted value, like "HP"
+    pub title: String,
+    /// not saved in scenes, `StatbarLabelFormat::Custom` closures can't be serialized
+    #[reflect(ignore)]
+    pub format: StatbarLabelFormat,
+    pub placement: StatbarLabelPlacement,
+    /// the label draws nothing until this is a loaded font
+    pub font: Handle<Font>,
+    pub font_size: f32,
+    pub color: Color,
+    /// absolute displacement from the label's placement
+    pub offset: Vec2,
+    /// current and maximum value of the observed stat
+    #[reflect(ignore)]
+    current_max: Option<(f32, f32)>,
+    #[reflect(ignore)]
+    text_entity: Option<Entity>,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarLabel<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl<T> StatbarLabel<T>
+where
+    T: 'static,
+{
+    /// A white percentage label centered on the bar
+    pub fn new(font: Handle<Font>) -> Self {
+        Self {
+            title: String::new(),
+            format: StatbarLabelFormat::Percentage,
+            placement: StatbarLabelPlacement::Center,
+            font,
+            font_size: 12.,
+            color: Color::WHITE,
+            offset: Vec2::ZERO,
+            current_max: None,
+            text_entity: None,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn with_format(mut self, format: StatbarLabelFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_placement(mut self, placement: StatbarLabelPlacement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    pub fn with_font_size(mut self, font_size: f32) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    fn text(&self, value: f32) -> String {
+        titled_text(&self.title, self.format.format(value, self.current_max))
+    }
+}
+
+/// Joins a title and a formatted value with a space, leaving out whichever is empty
+pub(crate) fn titled_text(title: &str, formatted: String) -> String {
+    match (title.is_empty(), formatted.is_empty()) {
+        (true, _) => formatted,
+        (false, true) => title.to_string(),
+        (false, false) => format!("{} {}", title, formatted),
+    }
+}
+
+impl<T> StatbarTextSource for StatbarLabel<T>
+where
+    T: 'static,
+{
+    fn text_entity(&self) -> Option<Entity> {
+        self.text_entity
+    }
+
+    fn set_text_entity(&mut self, text_entity: Entity) {
+        self.text_entity = Some(text_entity);
+    }
+
+    fn current_max(&self) -> Option<(f32, f32)> {
+        self.current_max
+    }
+
+    fn set_current_max(&mut self, current_max: Option<(f32, f32)>) {
+        self.current_max = current_max;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_statbar_labels<T>(
+    depth: Option<Res<StatbarDepth>>,
+    depth_order: Option<Res<StatbarDepthOrder>>,
+    declutter_state: Option<Res<StatbarDeclutterState>>,
+    lod_settings: Option<Res<StatbarLodSettings>>,
+    label_query: Query<(
+        Entity,
+        &Statbar<T>,
+        &StatbarLabel<T>,
+        ChangeTrackers<StatbarLabel<T>>,
+        Option<&StatbarBorder<T>>,
+        Option<&StatbarDepthOffset<T>>,
+        Option<&StatbarAutoHide<T>>,
+        Option<&StatbarEdgeClamp<T>>,
+        Option<&StatbarLod<T>>,
+        Option<&StatbarShake<T>>,
+        &GlobalTransform,
+        &ComputedVisibility,
+    )>,
+    mut text_query: Query<
+        (
+            &mut StatbarText<StatbarLabel<T>>,
+            &mut Text,
+            &mut Transform,
+            &mut GlobalTransform,
+            &mut Visibility,
+        ),
+        (Without<StatbarLabel<T>>, Without<Camera>),
+    >,
+    cameras: Query<(Entity, &Camera, &GlobalTransform)>,
+) where
+    T: 'static,
+{
+    let base_z = base_z(depth.as_deref());
+    let default_camera = default_camera(cameras.iter());
+    let zoom = default_camera
+        .and_then(|(_, camera, camera_transform)| camera_zoom(camera, camera_transform));
+    label_query.for_each(
+        |(
+            id,
+            bar,
+            label,
+            label_tracker,
+            border,
+            depth_offset,
+            auto_hide,
+            edge_clamp,
+            lod,
+            shake,
+            global_transform,
+            computed_visibility,
+        )| {
+            let text_entity = match label.text_entity {
+                Some(text_entity) => text_entity,
+                None => return,
+            };
+            let (
+                mut label_text,
+                mut text,
+                mut transform,
+                mut text_global_transform,
+                mut visibility,
+            ) = match text_query.get_mut(text_entity) {
+                Ok(text_components) => text_components,
+                Err(_) => return,
+            };
+
+            // labels are hidden whenever their statbar isn't drawn
+            let visible = if edge_clamp.is_some() {
+                computed_visibility.is_visible_in_hierarchy()
+            } else {
+                computed_visibility.is_visible()
+            };
+            let alpha = auto_hide.map(|auto_hide| auto_hide.alpha()).unwrap_or(1.);
+            let lod_level = statbar_lod_level(
+                lod.map(|lod| &lod.settings).or(lod_settings.as_deref()),
+                default_camera.map(|(_, _, camera_transform)| camera_transform),
+                zoom,
+                global_transform.translation().truncate() + bar.displacement,
+            );
+            let center =
+                if !bar.hide && visible && 0. < alpha && lod_level != StatbarLodLevel::Culled {
+                    statbar_center(id, bar, global_transform, declutter_state.as_deref())
+                } else {
+                    None
+                };
+            let is_visible = center.is_some();
+            if visibility.is_visible != is_visible {
+                visibility.is_visible = is_visible;
+            }
+            let mut center = match center {
+                Some(center) => center + shake.map(|shake| shake.offset()).unwrap_or_default(),
+                None => return,
+            };
+            let half_extents = 0.5 * statbar_outer_size(bar, border);
+            if let Some(edge_clamp) = edge_clamp {
+                let camera = match edge_clamp.camera {
+                    Some(camera_id) => cameras.get(camera_id).ok(),
+                    None => default_camera,
+                };
+                let clamped = camera.and_then(|(_, camera, camera_transform)| {
+                    clamp_to_viewport(
+                        camera,
+                        camera_transform,
+                        center.extend(global_transform.translation().z),
+                        half_extents,
+                        edge_clamp.margin,
+                    )
+                });
+                if let Some((position, _)) = clamped {
+                    center = position;
+                }
+            }
+
+            let (placement_offset, vertical, horizontal) = match label.placement {
+                StatbarLabelPlacement::Center => {
+                    (Vec2::ZERO, VerticalAlign::Center, HorizontalAlign::Center)
+                }
+                StatbarLabelPlacement::Above => (
+                    (half_extents.y + LABEL_GAP) * Vec2::Y,
+                    VerticalAlign::Bottom,
+                    HorizontalAlign::Center,
+                ),
+                StatbarLabelPlacement::Below => (
+                    -(half_extents.y + LABEL_GAP) * Vec2::Y,
+                    VerticalAlign::Top,
+                    HorizontalAlign::Center,
+                ),
+                StatbarLabelPlacement::Left => (
+                    -(half_extents.x + LABEL_GAP) * Vec2::X,
+                    VerticalAlign::Center,
+                    HorizontalAlign::Right,
+                ),
+                StatbarLabelPlacement::Right => (
+                    (half_extents.x + LABEL_GAP) * Vec2::X,
+                    VerticalAlign::Center,
+                    HorizontalAlign::Left,
+                ),
+            };
+
+            // labels aren't part of the transform hierarchy, like their statbars
+            let position = center + placement_offset + label.offset;
+            let z = statbar_layers(
+                id,
+                depth_order.as_deref(),
+                base_z,
+                depth_offset,
+                global_transform,
+            )
+            .z + 1.;
+            *transform = Transform::from_translation(position.extend(z));
+            *text_global_transform = GlobalTransform::from(*transform);
+
+            let displayed = Some((bar.value, label.current_max));
+            let color = fade_color(label.color, alpha);
+            let style_changed = text.sections.first().is_none_or(|section| {
+                section.style.font != label.font
+                    || section.style.font_size != label.font_size
+                    || section.style.color != color
+            });
+            if label_text.displayed != displayed || label_tracker.is_changed() || style_changed {
+                let style = TextStyle {
+                    font: label.font.clone(),
+                    font_size: label.font_size,
+                    color,
+                };
+                *text = Text::from_section(label.text(bar.value), style).with_alignment(
+                    TextAlignment {
+                        vertical,
+                        horizontal,
+                    },
+                );
+                label_text.displayed = displayed;
+            }
+        },
+    );
+}
@@ -1,16 +1,175 @@
+mod auto_hide;
+mod billboard;
+mod color_ramp;
+mod declutter;
+mod depth;
+mod edges;
+mod effects;
 mod extraction;
+mod fill_effect;
+mod fill_quad;
+mod hover;
+mod icon;
+mod label;
+mod lod;
+mod material;
+mod node;
+mod offscreen;
+mod palette;
+mod pattern;
+mod shadow;
+mod slider;
+mod style;
+mod text;
+mod theme;
+mod threshold;
 
+use bevy::ecs::entity::EntityMap;
+use bevy::ecs::entity::MapEntities;
+use bevy::ecs::entity::MapEntitiesError;
+use bevy::ecs::reflect::ReflectMapEntities;
+use bevy::ecs::world::FromWorld;
 use bevy::prelude::*;
+use bevy::reflect::FromReflect;
+use serde::Deserialize;
+use serde::Serialize;
 use std::marker::PhantomData;
 
+pub use auto_hide::StatbarAutoHide;
+pub use billboard::StatbarBillboard;
+pub use billboard::StatbarBillboardMaterial;
+pub use billboard::StatbarBillboardPlugin;
+pub use color_ramp::ColorInterpolation;
+pub use color_ramp::ColorRamp;
+pub use color_ramp::ColorStop;
+pub use color_ramp::StatbarColorRamp;
+pub use declutter::StatbarDeclutter;
+pub use declutter::StatbarDeclutterMode;
+pub use declutter::StatbarDeclutterSettings;
+pub use edges::StatbarEdgeMode;
+pub use effects::StatbarFlash;
+pub use effects::StatbarPulse;
+pub use effects::StatbarShake;
+pub use fill_effect::StatbarFillEffect;
+pub use fill_effect::StatbarFillEffectKind;
+pub use fill_effect::StatbarFillEffectPlugin;
+pub use fill_effect::StatbarFillMaterial;
+pub use fill_effect::StatbarFillUniform;
+pub use fill_quad::StatbarFillParams;
+pub use hover::StatbarHover;
+pub use hover::StatbarHovered;
+pub use hover::StatbarTooltip;
+pub use icon::StatbarIcon;
+pub use label::StatbarLabel;
+pub use label::StatbarLabelFormat;
+pub use label::StatbarLabelPlacement;
+pub use lod::StatbarLod;
+pub use lod::StatbarLodLevel;
+pub use lod::StatbarLodSettings;
+pub use material::StatbarMaterial;
+pub use material::StatbarMaterialHandle;
+pub use material::StatbarMaterialUniform;
+pub use node::StatbarNode;
+pub use node::StatbarNodeBundle;
+pub use offscreen::StatbarEdgeClamp;
+pub use palette::ColorVisionDeficiency;
+pub use palette::StatbarColorblindMode;
+pub use palette::StatbarPalette;
+pub use pattern::StatbarPattern;
+pub use pattern::StatbarPatternKind;
+pub use shadow::StatbarGlow;
+pub use shadow::StatbarShadow;
+pub use slider::StatbarSlider;
+pub use style::StatbarStyle;
+pub use style::StatbarStyleBorder;
+pub use style::StatbarStyleColorLerp;
+pub use style::StatbarStyleColorSwitch;
+pub use style::StatbarStyleHandle;
+pub use theme::StatbarFaction;
+pub use theme::StatbarTheme;
+pub use theme::StatbarUseTheme;
+pub use threshold::StatbarCrossing;
+pub use threshold::StatbarThresholdEvent;
+pub use threshold::StatbarThresholds;
+
 /// Insert as a resource to set z depth of Statbars
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct StatbarDepth(pub f32);
 
+/// Adjusts the z depth of an individual Statbar.
+///
+/// Statbars with a greater depth are drawn on top of Statbars with a lesser depth.
+/// Bars with the same depth are ordered by their entity id so overlapping bars
+/// are always drawn in the same order.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
+pub struct StatbarDepthOffset<T>
+where
+    T: 'static,
+{
+    /// added to the base depth of the statbar
+    pub offset: f32,
+    /// false => base depth is the `StatbarDepth` resource,
+    /// true => base depth is the z coordinate of the entity's `GlobalTransform`
+    pub relative: bool,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarDepthOffset<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(0.)
+    }
+}
+
+impl<T> StatbarDepthOffset<T>
+where
+    T: 'static,
+{
+    /// Draw the statbar at `offset` from the depth set by the `StatbarDepth` resource
+    pub fn new(offset: f32) -> Self {
+        Self {
+            offset,
+            relative: false,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Draw the statbar at `offset` from the z coordinate of its entity,
+    /// so that it is sorted along with the entity's sprite.
+    pub fn relative(offset: f32) -> Self {
+        Self {
+            offset,
+            relative: true,
+            phantom: PhantomData,
+        }
+    }
+}
+
 /// Implement `StatbarObservable` for a component you want to visualise with a stat bar.
 /// Should return a value between 0.0 (= empty) and 1.0 (= full).
 /// If the value is larger or smaller it is clamped before rendering.
 pub trait StatbarObservable {
     fn get_statbar_value(&self) -> f32;
+
+    /// Optionally implement to return the current and maximum value of the stat,
+    /// displayed by a `StatbarLabel` with the `StatbarLabelFormat::CurrentMax` format.
+    fn get_statbar_current_max(&self) -> Option<(f32, f32)> {
+        None
+    }
+}
+
+/// The writable counterpart of `StatbarObservable`,
+/// implement to let a `StatbarSlider` set the value of the observed stat.
+pub trait StatbarSettable: StatbarObservable {
+    /// Called with the value the slider was dragged to, from 0.0 to 1.0
+    fn set_statbar_value(&mut self, value: f32);
 }
 
 /// Insert this component to observe components from another entity.
@@ -22,12 +181,67 @@ pub trait StatbarObservable {
 /// Does not have a marker component because I think it would be very confusing to have
 /// an entity with three statbars observing components on three other entities.
 /// If you really want a many to one capability, it should be trivial to write your own system.
+///
+/// When loaded from a scene, an observed entity that wasn't saved in the scene is replaced
+/// with a placeholder that never exists, so the statbar stops updating instead of observing an unrelated entity.
 #[derive(Component, Reflect)]
+#[reflect(Component, MapEntities)]
 pub struct StatbarObserveEntity(pub Entity);
 
+/// Stands in for an entity that doesn't exist
+fn placeholder_entity() -> Entity {
+    Entity::from_raw(u32::MAX)
+}
+
+// required by `ReflectComponent`, the placeholder entity is replaced when the component is loaded from a scene
+impl FromWorld for StatbarObserveEntity {
+    fn from_world(_world: &mut World) -> Self {
+        StatbarObserveEntity(placeholder_entity())
+    }
+}
+
+// ids of entities that weren't saved in the scene could belong to any entity in the loading world
+impl MapEntities for StatbarObserveEntity {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        self.0 = entity_map
+            .get(self.0)
+            .unwrap_or_else(|_| placeholder_entity());
+        Ok(())
+    }
+}
+
+/// Despawns the entities marked with `M` whose statbar, `bar_of` the marker,
+/// no longer has a `C` that `owns` them
+pub(crate) fn despawn_orphans<M, C>(
+    commands: &mut Commands,
+    marker_query: &Query<(Entity, &M)>,
+    owner_query: &Query<&C>,
+    bar_of: impl Fn(&M) -> Entity,
+    owns: impl Fn(&C, Entity) -> bool,
+) where
+    M: Component,
+    C: Component,
+{
+    marker_query.for_each(|(entity, marker)| {
+        let orphaned = owner_query
+            .get(bar_of(marker))
+            .map_or(true, |owner| !owns(owner, entity));
+        if orphaned {
+            commands.entity(entity).despawn();
+        }
+    });
+}
+
+/// Maps an optional camera entity saved in a scene to the loaded camera.
+/// Cameras that weren't saved in the scene are replaced with None, the default camera.
+pub(crate) fn map_camera_entity(camera: &mut Option<Entity>, entity_map: &EntityMap) {
+    *camera = camera.and_then(|camera| entity_map.get(camera).ok());
+}
+
 /// Insert this component to observe components from the entities parent.
 /// Overrides any local obversable components and StatbarObservedEntity.
-#[derive(Component, Reflect)]
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
 pub struct StatbarObserveParent;
 
 // observe a resource that implements 'StatbarOversable'
@@ -56,6 +270,8 @@ where
 /// Multiple statbars can be inserted on a single entity by using different marker components.
 #[derive(Component, Reflect)]
 #[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
 pub struct Statbar<T = ()>
 where
     T: 'static,
@@ -89,6 +305,7 @@ where
     /// * 1.0 => bar entity colored with full color
     pub value: f32,
     #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
     #[doc(hidden)]
     pub _phantom: PhantomData<fn() -> T>,
 }
@@ -110,9 +327,32 @@ impl<T> Default for Statbar<T> {
     }
 }
 
+/// How a `StatbarBorder` is drawn.
+///
+/// Only sprite statbars draw every kind. `StatbarNode` bars always draw an `Outer` border
+/// with its side colors, and `StatbarBillboard` bars an `Outer` border in the border's `color`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect_value(PartialEq, Serialize, Deserialize)]
+pub enum StatbarBorderKind {
+    /// drawn around the outside of the bar
+    #[default]
+    Outer,
+    /// drawn over the edges of the bar, without changing its size
+    Inner,
+    /// two outer borders with a gap between them
+    Double {
+        /// width of the gap between the two borders
+        gap: f32,
+        /// color the gap is filled with, `Color::NONE` => transparent
+        gap_color: Color,
+    },
+}
+
 /// Adds a border around the corresponding Statbar
 #[derive(Clone, Copy, Debug, Component, Reflect)]
 #[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
 pub struct StatbarBorder<T>
 where
     T: 'static,
@@ -120,14 +360,26 @@ where
     /// color of the border
     pub color: Color,
     /// thickness of the border on the left
-    left: f32,
+    pub left: f32,
     /// thickness of the border on the right
-    right: f32,
+    pub right: f32,
     /// thickness of the border on the bottom
-    bottom: f32,
+    pub bottom: f32,
     /// thickness of the border on the top
-    top: f32,
+    pub top: f32,
+    /// color of the left side, None => `color`
+    pub left_color: Option<Color>,
+    /// color of the right side, None => `color`
+    pub right_color: Option<Color>,
+    /// color of the bottom side, None => `color`
+    pub bottom_color: Option<Color>,
+    /// color of the top side, None => `color`
+    pub top_color: Option<Color>,
+    /// whether the border is drawn outside the bar, over its edges or doubled,
+    /// billboards and UI nodes always draw an outer border in `color`
+    pub kind: StatbarBorderKind,
     #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
     phantom: PhantomData<fn() -> T>,
 }
 
@@ -135,17 +387,117 @@ impl<T> StatbarBorder<T>
 where
     T: 'static,
 {
-    /// A StarbarBorder with the same thickness on all four sides
-    pub fn all(color: Color, thickness: f32) -> Self {
+    /// A StatbarBorder with a different thickness on each side
+    pub fn new(color: Color, left: f32, right: f32, bottom: f32, top: f32) -> Self {
         Self {
             color,
-            left: thickness,
-            right: thickness,
-            bottom: thickness,
-            top: thickness,
+            left,
+            right,
+            bottom,
+            top,
+            left_color: None,
+            right_color: None,
+            bottom_color: None,
+            top_color: None,
+            kind: StatbarBorderKind::Outer,
             phantom: PhantomData,
         }
     }
+
+    /// A StarbarBorder with the same thickness on all four sides
+    pub fn all(color: Color, thickness: f32) -> Self {
+        Self::new(color, thickness, thickness, thickness, thickness)
+    }
+
+    /// A StatbarBorder with `horizontal` thickness on the left and right
+    /// and `vertical` thickness on the bottom and top
+    pub fn symmetric(color: Color, horizontal: f32, vertical: f32) -> Self {
+        Self::new(color, horizontal, horizontal, vertical, vertical)
+    }
+
+    /// A StatbarBorder on the left and right sides only
+    pub fn horizontal(color: Color, thickness: f32) -> Self {
+        Self::symmetric(color, thickness, 0.)
+    }
+
+    /// A StatbarBorder on the bottom and top sides only
+    pub fn vertical(color: Color, thickness: f32) -> Self {
+        Self::symmetric(color, 0., thickness)
+    }
+
+    pub fn with_left(mut self, thickness: f32) -> Self {
+        self.left = thickness;
+        self
+    }
+
+    pub fn with_right(mut self, thickness: f32) -> Self {
+        self.right = thickness;
+        self
+    }
+
+    pub fn with_bottom(mut self, thickness: f32) -> Self {
+        self.bottom = thickness;
+        self
+    }
+
+    pub fn with_top(mut self, thickness: f32) -> Self {
+        self.top = thickness;
+        self
+    }
+
+    /// Draw each side with its own color
+    pub fn with_side_colors(
+        mut self,
+        left: Color,
+        right: Color,
+        bottom: Color,
+        top: Color,
+    ) -> Self {
+        self.left_color = Some(left);
+        self.right_color = Some(right);
+        self.bottom_color = Some(bottom);
+        self.top_color = Some(top);
+        self
+    }
+
+    /// Draw the border over the edges of the bar instead of around it
+    pub fn inner(mut self) -> Self {
+        self.kind = StatbarBorderKind::Inner;
+        self
+    }
+
+    /// Draw the border twice, separated by a gap of `gap` filled with `gap_color`
+    pub fn double(mut self, gap: f32, gap_color: Color) -> Self {
+        self.kind = StatbarBorderKind::Double { gap, gap_color };
+        self
+    }
+
+    /// The colors of the left, right, bottom and top sides
+    pub fn side_colors(&self) -> [Color; 4] {
+        [
+            self.left_color.unwrap_or(self.color),
+            self.right_color.unwrap_or(self.color),
+            self.bottom_color.unwrap_or(self.color),
+            self.top_color.unwrap_or(self.color),
+        ]
+    }
+
+    /// How far the border extends past the left and bottom, and past the right and top, of the bar
+    pub fn outer_extents(&self) -> (Vec2, Vec2) {
+        let low = Vec2::new(self.left, self.bottom);
+        let high = Vec2::new(self.right, self.top);
+        match self.kind {
+            StatbarBorderKind::Outer => (low, high),
+            StatbarBorderKind::Inner => (Vec2::ZERO, Vec2::ZERO),
+            StatbarBorderKind::Double { gap, .. } => {
+                // sides without a border don't have a gap either
+                let with_gap = |sides: Vec2| {
+                    2. * sides + Vec2::select(sides.cmpgt(Vec2::ZERO), Vec2::splat(gap), Vec2::ZERO)
+                };
+                (with_gap(low), with_gap(high))
+            }
+        }
+    }
 }
 
 impl<T> Default for StatbarBorder<T>
@@ -157,12 +509,29 @@ where
     }
 }
 
+/// Which color of a statbar is set by a value-driven color rule
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Reflect, FromReflect, Serialize, Deserialize,
+)]
+#[reflect_value(PartialEq, Serialize, Deserialize)]
+pub enum StatbarColorTarget {
+    /// `Statbar::color`
+    #[default]
+    Fill,
+    /// `Statbar::empty_color`
+    Empty,
+    /// `StatbarBorder::color`
+    Border,
+}
+
 /// Linearly interpolate the value of the bar color
 /// between `min` and `max` using the value of the Statbar
 /// * statbar.value == 0. => statbar.color == min
 /// * statbar.value == 1. => statbar.color == max
-#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[derive(Clone, Copy, Debug, Component, Reflect, FromReflect)]
 #[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
 pub struct StatbarColorLerp<T>
 where
     T: 'static,
@@ -171,7 +540,12 @@ where
     pub min: Color,
     /// bar color when value is 1.0
     pub max: Color,
+    /// the color that is set, the fill color by default
+    pub target: StatbarColorTarget,
+    /// when several color rules set the same color, the rule with the highest priority is used
+    pub priority: i32,
     #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
     phantom: PhantomData<fn() -> T>,
 }
 
@@ -180,11 +554,7 @@ where
     T: 'static,
 {
     fn default() -> Self {
-        Self {
-            min: Color::RED,
-            max: Color::GREEN,
-            phantom: Default::default(),
-        }
+        Self::new(Color::RED, Color::GREEN)
     }
 }
 
@@ -196,17 +566,41 @@ where
         Self {
             min,
             max,
+            target: StatbarColorTarget::Fill,
+            priority: 0,
             phantom: Default::default(),
         }
     }
+
+    /// Lerps from the palette's `low` color to its `high` color
+    pub fn from_palette(palette: StatbarPalette) -> Self {
+        Self::new(palette.low, palette.high)
+    }
+
+    pub fn with_target(mut self, target: StatbarColorTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// The color for a statbar with this value
+    pub fn color(&self, value: f32) -> Color {
+        Vec4::from(self.min).lerp(self.max.into(), value).into()
+    }
 }
 
 /// Change the statbar color depending on the value of the statbar's subject
 ///
 /// Could be used for a health bar that
 /// turns to red when the character has less than 25% health remaining.
-#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[derive(Clone, Copy, Debug, Component, Reflect, FromReflect)]
 #[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
 pub struct StatbarColorSwitch<T>
 where
     T: 'static,
@@ -220,7 +614,12 @@ where
     pub low: Color,
     /// statbar color when the statbar's value is greater than pivot
     pub high: Color,
+    /// the color that is set, the fill color by default
+    pub target: StatbarColorTarget,
+    /// when several color rules set the same color, the rule with the highest priority is used
+    pub priority: i32,
     #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
     phantom: PhantomData<fn() -> T>,
 }
 
@@ -229,12 +628,7 @@ where
     T: 'static,
 {
     fn default() -> Self {
-        Self {
-            pivot: 0.25,
-            low: Color::RED,
-            high: Color::GREEN,
-            phantom: Default::default(),
-        }
+        Self::new(0.25, Color::RED, Color::GREEN)
     }
 }
 
@@ -247,43 +641,207 @@ where
             pivot,
             low,
             high,
+            target: StatbarColorTarget::Fill,
+            priority: 0,
             phantom: Default::default(),
         }
     }
+
+    /// Switches between the palette's `low` and `high` colors
+    pub fn from_palette(pivot: f32, palette: StatbarPalette) -> Self {
+        Self::new(pivot, palette.low, palette.high)
+    }
+
+    /// A border that turns to `low` when the value of the statbar is less than or equal to `pivot`
+    pub fn border(pivot: f32, low: Color, high: Color) -> Self {
+        Self::new(pivot, low, high).with_target(StatbarColorTarget::Border)
+    }
+
+    pub fn with_target(mut self, target: StatbarColorTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// The color for a statbar with this value
+    pub fn color(&self, value: f32) -> Color {
+        if value <= self.pivot {
+            self.low
+        } else {
+            self.high
+        }
+    }
 }
 
-fn switch_stat_bar_colors<T>(
-    mut color_switch_query: Query<
-        (&mut Statbar<T>, &mut StatbarColorSwitch<T>),
-        Changed<Statbar<T>>,
-    >,
-) where
+/// Any number of color switches and lerps for the corresponding Statbar,
+/// for bars that need more than one `StatbarColorSwitch` or `StatbarColorLerp`,
+/// like a fill lerp with a border switch, or switches with different priorities for the same color.
+///
+/// The rules are evaluated along with the statbar's own switch, lerp and ramp components.
+#[derive(Clone, Debug, Component, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
+pub struct StatbarColorRules<T>
+where
     T: 'static,
 {
-    color_switch_query.for_each_mut(|(mut bar, switcher)| {
-        bar.color = if bar.value <= switcher.pivot {
-            switcher.low
-        } else {
-            switcher.high
-        };
-    });
+    pub switches: Vec<StatbarColorSwitch<T>>,
+    pub lerps: Vec<StatbarColorLerp<T>>,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarColorRules<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self {
+            switches: Vec::new(),
+            lerps: Vec::new(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> StatbarColorRules<T>
+where
+    T: 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_switch(mut self, switch: StatbarColorSwitch<T>) -> Self {
+        self.switches.push(switch);
+        self
+    }
+
+    pub fn with_lerp(mut self, lerp: StatbarColorLerp<T>) -> Self {
+        self.lerps.push(lerp);
+        self
+    }
+}
+
+/// The winning color rule for one of the colors of a statbar
+#[derive(Clone, Copy)]
+struct ColorRuleResult {
+    priority: i32,
+    color: Color,
+}
+
+impl ColorRuleResult {
+    /// Rules are offered in the order switches, lerps, ramp,
+    /// so with equal priorities a ramp beats a lerp, which beats a switch.
+    /// The rules of a `StatbarColorRules` are offered in order after the component of the same kind,
+    /// so of rules of the same kind with equal priorities the last one wins.
+    fn offer(slot: &mut Option<Self>, priority: i32, color: Option<Color>) {
+        if let Some(color) = color {
+            if slot.is_none_or(|current| current.priority <= priority) {
+                *slot = Some(Self { priority, color });
+            }
+        }
+    }
 }
 
-fn lerp_stat_bar_colors<T>(
-    mut color_lerp_query: Query<(&mut Statbar<T>, &mut StatbarColorLerp<T>), Changed<Statbar<T>>>,
+/// Evaluates every color rule of a statbar, `StatbarColorSwitch`, `StatbarColorLerp`, `StatbarColorRamp`
+/// and the rules of its `StatbarColorRules`, and sets each targeted color from the rule with the highest priority.
+#[allow(clippy::type_complexity)]
+fn update_stat_bar_colors<T>(
+    mut color_query: Query<
+        (
+            &mut Statbar<T>,
+            Option<&mut StatbarBorder<T>>,
+            Option<&StatbarColorSwitch<T>>,
+            Option<&StatbarColorLerp<T>>,
+            Option<&StatbarColorRamp<T>>,
+            Option<&StatbarColorRules<T>>,
+        ),
+        (
+            Or<(
+                With<StatbarColorSwitch<T>>,
+                With<StatbarColorLerp<T>>,
+                With<StatbarColorRamp<T>>,
+                With<StatbarColorRules<T>>,
+            )>,
+            Or<(
+                Changed<Statbar<T>>,
+                Changed<StatbarBorder<T>>,
+                Changed<StatbarColorSwitch<T>>,
+                Changed<StatbarColorLerp<T>>,
+                Changed<StatbarColorRamp<T>>,
+                Changed<StatbarColorRules<T>>,
+            )>,
+        ),
+    >,
 ) where
     T: 'static,
 {
-    color_lerp_query.for_each_mut(|(mut bar, lerper)| {
-        bar.color = Vec4::from(lerper.min)
-            .lerp(lerper.max.into(), bar.value)
-            .into();
+    color_query.for_each_mut(|(mut bar, border, switcher, lerper, ramp, rules)| {
+        let value = bar.value;
+        // fill, empty, border
+        let mut results: [Option<ColorRuleResult>; 3] = [None; 3];
+        let slot = |target: StatbarColorTarget| match target {
+            StatbarColorTarget::Fill => 0,
+            StatbarColorTarget::Empty => 1,
+            StatbarColorTarget::Border => 2,
+        };
+        let rule_switches = rules.into_iter().flat_map(|rules| &rules.switches);
+        for switcher in switcher.into_iter().chain(rule_switches) {
+            ColorRuleResult::offer(
+                &mut results[slot(switcher.target)],
+                switcher.priority,
+                Some(switcher.color(value)),
+            );
+        }
+        let rule_lerps = rules.into_iter().flat_map(|rules| &rules.lerps);
+        for lerper in lerper.into_iter().chain(rule_lerps) {
+            ColorRuleResult::offer(
+                &mut results[slot(lerper.target)],
+                lerper.priority,
+                Some(lerper.color(value)),
+            );
+        }
+        if let Some(ramp) = ramp {
+            let value = value.clamp(0., 1.);
+            ColorRuleResult::offer(&mut results[0], ramp.priority, ramp.fill.sample(value));
+            ColorRuleResult::offer(&mut results[1], ramp.priority, ramp.empty.sample(value));
+            ColorRuleResult::offer(&mut results[2], ramp.priority, ramp.border.sample(value));
+        }
+
+        let [fill, empty, border_color] = results.map(|result| result.map(|result| result.color));
+        if let Some(color) = fill {
+            if bar.color != color {
+                bar.color = color;
+            }
+        }
+        if let Some(color) = empty {
+            if bar.empty_color != color {
+                bar.empty_color = color;
+            }
+        }
+        if let (Some(mut border), Some(color)) = (border, border_color) {
+            if border.color != color {
+                border.color = color;
+            }
+        }
     });
 }
 
+#[allow(clippy::type_complexity)]
 fn update_statbar_values<T>(
     mut statbar_query: Query<
-        (&mut Statbar<T>, &T),
+        (
+            &mut Statbar<T>,
+            &T,
+            (Option<&mut StatbarLabel<T>>, Option<&mut StatbarTooltip<T>>),
+        ),
         (
             Changed<T>,
             Without<StatbarObserveParent>,
@@ -293,83 +851,388 @@ fn update_statbar_values<T>(
 ) where
     T: Component + StatbarObservable,
 {
-    statbar_query.for_each_mut(|(mut statbar, value)| {
+    statbar_query.for_each_mut(|(mut statbar, value, (label, tooltip))| {
         statbar.value = value.get_statbar_value();
+        let current_max = value.get_statbar_current_max();
+        text::update_text_current_max(label, current_max);
+        text::update_text_current_max(tooltip, current_max);
     });
 }
 
+#[allow(clippy::type_complexity)]
 fn update_statbar_values_from_parents<T>(
     mut statbar_query: Query<
-        (&mut Statbar<T>, &Parent),
+        (
+            &mut Statbar<T>,
+            &Parent,
+            (Option<&mut StatbarLabel<T>>, Option<&mut StatbarTooltip<T>>),
+        ),
         (With<StatbarObserveParent>, Without<StatbarObserveEntity>),
     >,
     parent_value_query: Query<&T, Changed<T>>,
 ) where
     T: Component + StatbarObservable,
 {
-    statbar_query.for_each_mut(|(mut statbar, parent)| {
+    statbar_query.for_each_mut(|(mut statbar, parent, (label, tooltip))| {
         if let Ok(value) = parent_value_query.get(parent.get()) {
             statbar.value = value.get_statbar_value();
+            let current_max = value.get_statbar_current_max();
+            text::update_text_current_max(label, current_max);
+            text::update_text_current_max(tooltip, current_max);
         }
     });
 }
 
+#[allow(clippy::type_complexity)]
 fn update_statbar_values_from_other<T>(
     mut statbar_query: Query<
-        (&mut Statbar<T>, &StatbarObserveEntity),
+        (
+            &mut Statbar<T>,
+            &StatbarObserveEntity,
+            (Option<&mut StatbarLabel<T>>, Option<&mut StatbarTooltip<T>>),
+        ),
         Without<StatbarObserveParent>,
     >,
     other_value_query: Query<&T, Changed<T>>,
 ) where
     T: Component + StatbarObservable,
 {
-    statbar_query.for_each_mut(|(mut statbar, &StatbarObserveEntity(target))| {
-        if let Ok(value) = other_value_query.get(target) {
-            statbar.value = value.get_statbar_value();
-        }
-    });
+    statbar_query.for_each_mut(
+        |(mut statbar, &StatbarObserveEntity(target), (label, tooltip))| {
+            if let Ok(value) = other_value_query.get(target) {
+                statbar.value = value.get_statbar_value();
+                let current_max = value.get_statbar_current_max();
+                text::update_text_current_max(label, current_max);
+                text::update_text_current_max(tooltip, current_max);
+            }
+        },
+    );
 }
 
-fn update_statbar_from_resource<T>(resource: Res<T>, mut statbar_query: Query<&mut Statbar<T>>)
-where
+#[allow(clippy::type_complexity)]
+fn update_statbar_from_resource<T>(
+    resource: Res<T>,
+    mut statbar_query: Query<(
+        &mut Statbar<T>,
+        (Option<&mut StatbarLabel<T>>, Option<&mut StatbarTooltip<T>>),
+    )>,
+) where
     T: StatbarObservable + 'static + Send + Sync,
 {
     if resource.is_changed() {
-        statbar_query.for_each_mut(|mut statbar| {
+        statbar_query.for_each_mut(|(mut statbar, (label, tooltip))| {
             statbar.value = resource.get_statbar_value();
+            let current_max = resource.get_statbar_current_max();
+            text::update_text_current_max(label, current_max);
+            text::update_text_current_max(tooltip, current_max);
         });
     }
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
 pub enum StatbarSystem {
+    ApplyTheme,
+    ApplyStyles,
+    UpdateSliders,
     UpdateValues,
     UpdateColors,
+    UpdateVisibility,
+    UpdateEffects,
+    UpdateLabels,
+    UpdateHover,
+    UpdateNodes,
+    UpdateBillboards,
+    UpdateFillEffects,
+    SendThresholdEvents,
+    CollectBounds,
+    Declutter,
+    CollectDepths,
+    OrderDepths,
     ExtractSprites,
 }
 
+/// Adds the systems and registers the types used by every kind of statbar
+fn add_statbar_common<T: 'static>(app: &mut App) -> &mut App {
+    if let Ok(render_app) = app.get_sub_app_mut(bevy::render::RenderApp) {
+        render_app.add_system_to_stage(
+            bevy::render::RenderStage::Extract,
+            extraction::extract_stat_bars::<T>.after(bevy::sprite::SpriteSystem::ExtractSprites),
+        );
+    }
+
+    if !app.world.contains_resource::<depth::StatbarDepthOrder>() {
+        app.init_resource::<depth::StatbarDepthOrder>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                depth::order_statbar_depths
+                    .after(StatbarSystem::CollectDepths)
+                    .label(StatbarSystem::OrderDepths),
+            );
+    }
+
+    if !app
+        .world
+        .contains_resource::<declutter::StatbarDeclutterState>()
+    {
+        app.init_resource::<declutter::StatbarDeclutterState>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                declutter::declutter_statbars
+                    .after(StatbarSystem::CollectBounds)
+                    .label(StatbarSystem::Declutter),
+            );
+    }
+
+    // headless apps without the asset or render plugins can still update statbars, they just aren't drawn
+    if app.world.contains_resource::<AssetServer>()
+        && !app.world.contains_resource::<Assets<StatbarStyle>>()
+    {
+        app.add_asset::<StatbarStyle>()
+            .init_asset_loader::<style::StatbarStyleLoader>();
+    }
+
+    if let Some(mut images) = app.world.get_resource_mut::<Assets<Image>>() {
+        if !images.contains(&pattern::StatbarPatternKind::Stripes.image().typed()) {
+            pattern::add_pattern_images(&mut images);
+            shadow::add_soft_edge_image(&mut images);
+        }
+    }
+
+    app.register_type::<Statbar<T>>()
+        .register_type::<StatbarBorder<T>>()
+        .register_type::<StatbarColorLerp<T>>()
+        .register_type::<StatbarColorSwitch<T>>()
+        .register_type::<StatbarColorRamp<T>>()
+        .register_type::<StatbarColorRules<T>>()
+        .register_type::<StatbarDepthOffset<T>>()
+        .register_type::<StatbarEdgeClamp<T>>()
+        .register_type::<StatbarDeclutter<T>>()
+        .register_type::<StatbarAutoHide<T>>()
+        .register_type::<StatbarLodSettings>()
+        .register_type::<StatbarLod<T>>()
+        .register_type::<StatbarLabel<T>>()
+        .register_type::<StatbarIcon<T>>()
+        .register_type::<StatbarPattern<T>>()
+        .register_type::<StatbarShadow<T>>()
+        .register_type::<StatbarGlow<T>>()
+        .register_type::<StatbarThresholds<T>>()
+        .register_type::<StatbarFlash<T>>()
+        .register_type::<StatbarPulse<T>>()
+        .register_type::<StatbarShake<T>>()
+        .register_type::<StatbarSlider<T>>()
+        .register_type::<StatbarHover<T>>()
+        .register_type::<StatbarTooltip<T>>()
+        .register_type::<StatbarNode<T>>()
+        .register_type::<StatbarBillboard<T>>()
+        .register_type::<StatbarFillEffect<T>>()
+        .register_type::<StatbarStyleHandle<T>>()
+        .register_type::<StatbarUseTheme<T>>()
+        .register_type::<StatbarFaction>()
+        .register_type::<StatbarObserveEntity>()
+        .register_type::<StatbarObserveParent>()
+        .register_type::<StatbarColorTarget>()
+        .register_type::<StatbarBorderKind>()
+        .register_type::<Color>()
+        .register_type::<Option<Color>>()
+        .register_type_data::<Option<Color>, ReflectSerialize>()
+        .register_type_data::<Option<Color>, ReflectDeserialize>()
+        .register_type::<ColorInterpolation>()
+        .register_type::<ColorStop>()
+        .register_type::<ColorRamp>()
+        .register_type::<StatbarLabelPlacement>()
+        .register_type::<StatbarPatternKind>()
+        .register_type::<StatbarFillEffectKind>()
+        .register_type::<Option<Entity>>()
+        .register_type_data::<Option<Entity>, ReflectSerialize>()
+        .register_type_data::<Option<Entity>, ReflectDeserialize>()
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            theme::apply_statbar_theme::<T>
+                .before(StatbarSystem::ApplyStyles)
+                .before(StatbarSystem::UpdateColors)
+                .label(StatbarSystem::ApplyTheme),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            style::apply_statbar_styles::<T>
+                .before(StatbarSystem::UpdateColors)
+                .label(StatbarSystem::ApplyStyles),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            billboard::spawn_statbar_billboards::<T>.label(StatbarSystem::UpdateBillboards),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            billboard::despawn_orphaned_statbar_billboards::<T>
+                .label(StatbarSystem::UpdateBillboards),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            billboard::update_statbar_billboards::<T>
+                .after(StatbarSystem::UpdateColors)
+                .after(StatbarSystem::UpdateVisibility)
+                .after(bevy::transform::TransformSystem::TransformPropagate)
+                .after(bevy::render::view::VisibilitySystems::VisibilityPropagate)
+                // so the quads that are shown or hidden are culled this frame
+                .before(bevy::render::view::VisibilitySystems::CheckVisibility)
+                .label(StatbarSystem::UpdateBillboards),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            fill_effect::spawn_statbar_fill_effects::<T>.label(StatbarSystem::UpdateFillEffects),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            fill_effect::despawn_orphaned_statbar_fill_effects::<T>
+                .label(StatbarSystem::UpdateFillEffects),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            fill_effect::update_statbar_fill_effects::<T>
+                .after(StatbarSystem::OrderDepths)
+                .after(StatbarSystem::UpdateColors)
+                .after(StatbarSystem::UpdateEffects)
+                .after(StatbarSystem::Declutter)
+                .after(bevy::transform::TransformSystem::TransformPropagate)
+                .label(StatbarSystem::UpdateFillEffects),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            node::spawn_statbar_node_children::<T>.label(StatbarSystem::UpdateNodes),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            node::update_statbar_nodes::<T>
+                .after(StatbarSystem::UpdateColors)
+                .after(StatbarSystem::UpdateEffects)
+                .after(StatbarSystem::UpdateVisibility)
+                .before(bevy::ui::UiSystem::Flex)
+                .label(StatbarSystem::UpdateNodes),
+        )
+        .add_system_to_stage(
+            CoreStage::PreUpdate,
+            slider::update_statbar_sliders::<T>
+                .after(bevy::input::InputSystem)
+                .label(StatbarSystem::UpdateSliders),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            update_stat_bar_colors::<T>
+                .after(StatbarSystem::UpdateValues)
+                .label(StatbarSystem::UpdateColors),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            effects::update_statbar_flashes::<T>
+                .after(StatbarSystem::UpdateValues)
+                .label(StatbarSystem::UpdateEffects),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            effects::update_statbar_pulses::<T>
+                .after(StatbarSystem::UpdateValues)
+                .label(StatbarSystem::UpdateEffects),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            effects::update_statbar_shakes::<T>
+                .after(StatbarSystem::UpdateValues)
+                .label(StatbarSystem::UpdateEffects),
+        )
+        .add_event::<StatbarThresholdEvent<T>>()
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            threshold::send_statbar_threshold_events::<T>
+                .after(StatbarSystem::UpdateValues)
+                .label(StatbarSystem::SendThresholdEvents),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            text::spawn_statbar_texts::<StatbarLabel<T>>.label(StatbarSystem::UpdateLabels),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            text::despawn_orphaned_statbar_texts::<StatbarLabel<T>>
+                .label(StatbarSystem::UpdateLabels),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            label::update_statbar_labels::<T>
+                .after(StatbarSystem::OrderDepths)
+                .after(StatbarSystem::UpdateValues)
+                .after(StatbarSystem::UpdateEffects)
+                .after(StatbarSystem::UpdateVisibility)
+                .after(StatbarSystem::Declutter)
+                .after(bevy::transform::TransformSystem::TransformPropagate)
+                .label(StatbarSystem::UpdateLabels),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            hover::update_statbar_hover::<T>
+                .after(StatbarSystem::Declutter)
+                .after(bevy::transform::TransformSystem::TransformPropagate)
+                .label(StatbarSystem::UpdateHover),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            text::spawn_statbar_texts::<StatbarTooltip<T>>.label(StatbarSystem::UpdateLabels),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            text::despawn_orphaned_statbar_texts::<StatbarTooltip<T>>
+                .label(StatbarSystem::UpdateLabels),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            hover::update_statbar_tooltips::<T>
+                .after(StatbarSystem::OrderDepths)
+                .after(StatbarSystem::UpdateValues)
+                .after(StatbarSystem::UpdateHover)
+                .label(StatbarSystem::UpdateLabels),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            auto_hide::update_statbar_auto_hide::<T>
+                .after(StatbarSystem::UpdateValues)
+                .label(StatbarSystem::UpdateVisibility),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            depth::collect_statbar_depths::<T>
+                .after(bevy::transform::TransformSystem::TransformPropagate)
+                .label(StatbarSystem::CollectDepths),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            declutter::collect_statbar_bounds::<T>
+                .after(bevy::render::view::VisibilitySystems::CheckVisibility)
+                .after(StatbarSystem::UpdateVisibility)
+                .label(StatbarSystem::CollectBounds),
+        )
+}
+
 pub trait RegisterStatbarSubject {
     fn add_statbar_component_observer<T: StatbarObservable + Component>(&mut self) -> &mut Self;
     fn add_statbar_resource_observer<T: StatbarObservable + 'static + Send + Sync>(
         &mut self,
     ) -> &mut Self;
     fn add_standalone_statbar<T: 'static>(&mut self) -> &mut Self;
+    /// Call after `add_statbar_component_observer` to let sliders set the observed components
+    fn add_statbar_component_slider<T: StatbarSettable + Component>(&mut self) -> &mut Self;
+    /// Call after `add_statbar_resource_observer` to let sliders set the observed resource
+    fn add_statbar_resource_slider<T: StatbarSettable + 'static + Send + Sync>(
+        &mut self,
+    ) -> &mut Self;
+    /// Call after registering `T` to draw the fills of statbars with a `StatbarMaterialHandle<T, M>`
+    fn add_statbar_material<T: 'static, M: StatbarMaterial>(&mut self) -> &mut Self
+    where
+        M::Data: PartialEq + Eq + std::hash::Hash + Clone;
 }
 
 impl RegisterStatbarSubject for App {
     fn add_statbar_component_observer<T: StatbarObservable + Component>(&mut self) -> &mut Self {
-        if let Ok(render_app) = self.get_sub_app_mut(bevy::render::RenderApp) {
-            render_app.add_system_to_stage(
-                bevy::render::RenderStage::Extract,
-                extraction::extract_stat_bars::<T>
-                    .after(bevy::sprite::SpriteSystem::ExtractSprites),
-            );
-        }
-
-        self.register_type::<Statbar<T>>()
-            .register_type::<StatbarBorder<T>>()
-            .register_type::<StatbarColorLerp<T>>()
-            .register_type::<StatbarColorSwitch<T>>()
+        add_statbar_common::<T>(self)
             .add_system_to_stage(
                 CoreStage::PostUpdate,
                 update_statbar_values::<T>.label(StatbarSystem::UpdateValues),
@@ -382,73 +1245,63 @@ impl RegisterStatbarSubject for App {
                 CoreStage::PostUpdate,
                 update_statbar_values_from_parents::<T>.label(StatbarSystem::UpdateValues),
             )
-            .add_system_to_stage(
-                CoreStage::PostUpdate,
-                switch_stat_bar_colors::<T>
-                    .after(StatbarSystem::UpdateValues)
-                    .label(StatbarSystem::UpdateColors),
-            )
-            .add_system_to_stage(
-                CoreStage::PostUpdate,
-                lerp_stat_bar_colors::<T>
-                    .after(StatbarSystem::UpdateValues)
-                    .label(StatbarSystem::UpdateColors),
-            )
     }
 
     fn add_statbar_resource_observer<T: StatbarObservable + 'static + Send + Sync>(
         &mut self,
     ) -> &mut Self {
-        if let Ok(render_app) = self.get_sub_app_mut(bevy::render::RenderApp) {
-            render_app.add_system_to_stage(
-                bevy::render::RenderStage::Extract,
-                extraction::extract_stat_bars::<T>
-                    .after(bevy::sprite::SpriteSystem::ExtractSprites),
-            );
-        }
-
-        self.register_type::<Statbar<T>>()
-            .register_type::<StatbarBorder<T>>()
-            .register_type::<StatbarColorLerp<T>>()
-            .register_type::<StatbarColorSwitch<T>>()
-            .add_system_to_stage(
-                CoreStage::PostUpdate,
-                update_statbar_from_resource::<T>.label(StatbarSystem::UpdateValues),
-            )
-            .add_system_to_stage(
-                CoreStage::PostUpdate,
-                switch_stat_bar_colors::<T>
-                    .after(StatbarSystem::UpdateValues)
-                    .label(StatbarSystem::UpdateColors),
-            )
-            .add_system_to_stage(
-                CoreStage::PostUpdate,
-                lerp_stat_bar_colors::<T>
-                    .after(StatbarSystem::UpdateValues)
-                    .label(StatbarSystem::UpdateColors),
-            )
+        add_statbar_common::<T>(self).add_system_to_stage(
+            CoreStage::PostUpdate,
+            update_statbar_from_resource::<T>.label(StatbarSystem::UpdateValues),
+        )
     }
 
     fn add_standalone_statbar<T: 'static>(&mut self) -> &mut Self {
-        if let Ok(render_app) = self.get_sub_app_mut(bevy::render::RenderApp) {
-            render_app.add_system_to_stage(
-                bevy::render::RenderStage::Extract,
-                extraction::extract_stat_bars::<T>
-                    .after(bevy::sprite::SpriteSystem::ExtractSprites),
-            );
-        }
+        add_statbar_common::<T>(self)
+    }
 
-        self.register_type::<Statbar<T>>()
-            .register_type::<StatbarBorder<T>>()
-            .register_type::<StatbarColorLerp<T>>()
-            .register_type::<StatbarColorSwitch<T>>()
-            .add_system_to_stage(
-                CoreStage::PostUpdate,
-                switch_stat_bar_colors::<T>.label(StatbarSystem::UpdateColors),
-            )
-            .add_system_to_stage(
-                CoreStage::PostUpdate,
-                lerp_stat_bar_colors::<T>.label(StatbarSystem::UpdateColors),
-            )
+    fn add_statbar_component_slider<T: StatbarSettable + Component>(&mut self) -> &mut Self {
+        self.add_system_to_stage(
+            CoreStage::PreUpdate,
+            slider::write_statbar_slider_values::<T>.after(StatbarSystem::UpdateSliders),
+        )
+    }
+
+    fn add_statbar_resource_slider<T: StatbarSettable + 'static + Send + Sync>(
+        &mut self,
+    ) -> &mut Self {
+        self.add_system_to_stage(
+            CoreStage::PreUpdate,
+            slider::write_statbar_slider_resource_values::<T>.after(StatbarSystem::UpdateSliders),
+        )
+    }
+
+    fn add_statbar_material<T: 'static, M: StatbarMaterial>(&mut self) -> &mut Self
+    where
+        M::Data: PartialEq + Eq + std::hash::Hash + Clone,
+    {
+        if !self.world.contains_resource::<Assets<M>>() {
+            self.add_plugin(bevy::sprite::Material2dPlugin::<M>::default());
+        }
+        fill_quad::init_statbar_fill_mesh(self);
+        self.add_system_to_stage(
+            CoreStage::PostUpdate,
+            material::spawn_statbar_materials::<T, M>.label(StatbarSystem::UpdateFillEffects),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            material::despawn_orphaned_statbar_materials::<T, M>
+                .label(StatbarSystem::UpdateFillEffects),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            material::update_statbar_materials::<T, M>
+                .after(StatbarSystem::OrderDepths)
+                .after(StatbarSystem::UpdateColors)
+                .after(StatbarSystem::UpdateEffects)
+                .after(StatbarSystem::Declutter)
+                .after(bevy::transform::TransformSystem::TransformPropagate)
+                .label(StatbarSystem::UpdateFillEffects),
+        )
     }
 }
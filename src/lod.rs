@@ -0,0 +1,143 @@
+use bevy::prelude::*;
+#[cfg(feature = "serialize")]
+use serde::Deserialize;
+#[cfg(feature = "serialize")]
+use serde::Serialize;
+use std::marker::PhantomData;
+
+/// How much detail a statbar is drawn with
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StatbarLodLevel {
+    /// border, back and bar
+    Full,
+    /// back and bar
+    NoBorder,
+    /// just the bar
+    SingleQuad,
+    /// not drawn
+    Culled,
+}
+
+/// Distance and zoom thresholds for reducing the detail statbars are drawn with.
+///
+/// Insert as a resource to set the thresholds for every statbar,
+/// or use a `StatbarLod` component to set the thresholds for an individual statbar.
+///
+/// Distances are measured in world units in the xy plane, from the
+/// position of the statbar to the camera with the highest priority.
+/// Zoom is measured in logical pixels per world unit.
+#[derive(Clone, Copy, Debug, Reflect)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct StatbarLodSettings {
+    /// borders aren't drawn beyond this distance
+    pub no_border_distance: f32,
+    /// only the bar is drawn beyond this distance
+    pub single_quad_distance: f32,
+    /// nothing is drawn beyond this distance
+    pub cull_distance: f32,
+    /// borders aren't drawn below this zoom
+    pub no_border_zoom: f32,
+    /// only the bar is drawn below this zoom
+    pub single_quad_zoom: f32,
+    /// nothing is drawn below this zoom
+    pub cull_zoom: f32,
+}
+
+impl Default for StatbarLodSettings {
+    /// Thresholds that are never reached, statbars are always drawn with full detail.
+    fn default() -> Self {
+        Self {
+            no_border_distance: f32::INFINITY,
+            single_quad_distance: f32::INFINITY,
+            cull_distance: f32::INFINITY,
+            no_border_zoom: 0.,
+            single_quad_zoom: 0.,
+            cull_zoom: 0.,
+        }
+    }
+}
+
+impl StatbarLodSettings {
+    /// The level of detail for a statbar `distance` from the camera, at `zoom`
+    pub fn level(&self, distance: f32, zoom: f32) -> StatbarLodLevel {
+        let by_distance = if self.cull_distance < distance {
+            StatbarLodLevel::Culled
+        } else if self.single_quad_distance < distance {
+            StatbarLodLevel::SingleQuad
+        } else if self.no_border_distance < distance {
+            StatbarLodLevel::NoBorder
+        } else {
+            StatbarLodLevel::Full
+        };
+        let by_zoom = if zoom < self.cull_zoom {
+            StatbarLodLevel::Culled
+        } else if zoom < self.single_quad_zoom {
+            StatbarLodLevel::SingleQuad
+        } else if zoom < self.no_border_zoom {
+            StatbarLodLevel::NoBorder
+        } else {
+            StatbarLodLevel::Full
+        };
+        by_distance.max(by_zoom)
+    }
+}
+
+/// Overrides the `StatbarLodSettings` resource for the corresponding Statbar
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
+pub struct StatbarLod<T>
+where
+    T: 'static,
+{
+    pub settings: StatbarLodSettings,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarLod<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl<T> StatbarLod<T>
+where
+    T: 'static,
+{
+    pub fn new(settings: StatbarLodSettings) -> Self {
+        Self {
+            settings,
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// Logical pixels per world unit along the x axis of the camera's viewport
+pub(crate) fn camera_zoom(camera: &Camera, camera_transform: &GlobalTransform) -> Option<f32> {
+    let viewport_size = camera.logical_viewport_size()?;
+    let world_to_ndc = camera.projection_matrix() * camera_transform.compute_matrix().inverse();
+    Some(0.5 * viewport_size.x * world_to_ndc.transform_vector3(Vec3::X).length())
+}
+
+/// The level of detail of a statbar centered at `position`, seen from the camera at `camera_transform`,
+/// always `Full` without settings or a camera
+pub(crate) fn statbar_lod_level(
+    settings: Option<&StatbarLodSettings>,
+    camera_transform: Option<&GlobalTransform>,
+    zoom: Option<f32>,
+    position: Vec2,
+) -> StatbarLodLevel {
+    match (settings, camera_transform, zoom) {
+        (Some(settings), Some(camera_transform), Some(zoom)) => {
+            let distance = camera_transform.translation().truncate().distance(position);
+            settings.level(distance, zoom)
+        }
+        _ => StatbarLodLevel::Full,
+    }
+}
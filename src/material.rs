@@ -0,0 +1,202 @@
+use crate::fill_quad::despawn_orphaned_fill_quads;
+use crate::fill_quad::fill_quad;
+use crate::fill_quad::place_fill_quad;
+use crate::fill_quad::spawn_fill_quad;
+use crate::fill_quad::FillQuadFilter;
+use crate::fill_quad::FillQuadItem;
+use crate::fill_quad::FillQuadResources;
+use crate::fill_quad::StatbarCustomFill;
+use crate::fill_quad::StatbarFillMesh;
+use crate::fill_quad::StatbarFillQuad;
+use crate::*;
+use bevy::sprite::Material2d;
+use std::marker::PhantomData;
+
+/// Implement for a `Material2d` to draw the fill of statbars with a custom shader.
+///
+/// The material is drawn on a quad covering the whole bar, not including its border,
+/// above the empty part of the bar. The statbar's own fill sprite isn't drawn,
+/// so the shader decides which part of the bar is full.
+/// On the quad's uv coordinates, (0, 0) is the top left corner of the bar.
+pub trait StatbarMaterial: Material2d {
+    /// Called every frame the statbar is drawn, with the current state of the bar
+    fn set_statbar_params(&mut self, params: &StatbarFillParams);
+}
+
+pub use uniform::StatbarMaterialUniform;
+
+// the size checks generated by `ShaderType` are never called
+#[allow(dead_code)]
+mod uniform {
+    use bevy::prelude::*;
+    use bevy::render::render_resource::ShaderType;
+
+    /// The `StatbarFillParams` as a shader uniform, for materials that just need to pass them on.
+    ///
+    /// In WGSL:
+    /// ```wgsl
+    /// struct StatbarMaterialUniform {
+    ///     color: vec4<f32>,
+    ///     empty_color: vec4<f32>,
+    ///     size: vec2<f32>,
+    ///     value: f32,
+    ///     time: f32,
+    ///     flags: u32,
+    /// };
+    /// ```
+    #[derive(Clone, Copy, Debug, Default, ShaderType)]
+    pub struct StatbarMaterialUniform {
+        /// linear RGBA
+        pub color: Vec4,
+        /// linear RGBA
+        pub empty_color: Vec4,
+        /// size of the bar in world units
+        pub size: Vec2,
+        pub value: f32,
+        pub time: f32,
+        /// 1 => vertical, 2 => reverse
+        pub flags: u32,
+    }
+}
+
+impl From<&StatbarFillParams> for StatbarMaterialUniform {
+    fn from(params: &StatbarFillParams) -> Self {
+        Self {
+            color: Vec4::from(params.color.as_linear_rgba_f32()),
+            empty_color: Vec4::from(params.empty_color.as_linear_rgba_f32()),
+            size: params.size,
+            value: params.value,
+            time: params.time,
+            flags: params.vertical as u32 | (params.reverse as u32) << 1,
+        }
+    }
+}
+
+/// Draws the fill of the corresponding Statbar with a `StatbarMaterial`.
+///
+/// Register the material with `add_statbar_material`.
+/// The material is updated with the state of this statbar every frame,
+/// so each statbar needs its own material, don't share handles between bars.
+/// Not drawn for statbars with a `StatbarNode` or `StatbarBillboard`.
+#[derive(Clone, Debug, Component)]
+pub struct StatbarMaterialHandle<T, M>
+where
+    T: 'static,
+    M: StatbarMaterial,
+{
+    pub handle: Handle<M>,
+    /// quad drawing the fill
+    quad: Option<Entity>,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T, M> StatbarMaterialHandle<T, M>
+where
+    T: 'static,
+    M: StatbarMaterial,
+{
+    pub fn new(handle: Handle<M>) -> Self {
+        Self {
+            handle,
+            quad: None,
+            phantom: PhantomData,
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn spawn_statbar_materials<T, M>(
+    mut commands: Commands,
+    mesh: Option<Res<StatbarFillMesh>>,
+    mut material_query: Query<
+        (Entity, &mut StatbarMaterialHandle<T, M>),
+        Changed<StatbarMaterialHandle<T, M>>,
+    >,
+) where
+    T: 'static,
+    M: StatbarMaterial,
+{
+    let mesh = match mesh {
+        Some(mesh) => mesh,
+        None => return,
+    };
+    material_query.for_each_mut(|(bar, mut material)| {
+        if material.quad.is_some() {
+            return;
+        }
+        let handle = material.handle.clone();
+        material.quad = Some(spawn_fill_quad::<T, StatbarMaterialHandle<T, M>, _>(
+            &mut commands,
+            &mesh,
+            bar,
+            handle,
+        ));
+    });
+}
+
+pub(crate) fn despawn_orphaned_statbar_materials<T, M>(
+    commands: Commands,
+    removed: RemovedComponents<StatbarMaterialHandle<T, M>>,
+    quad_query: Query<(Entity, &StatbarFillQuad<StatbarMaterialHandle<T, M>>)>,
+    material_query: Query<&StatbarMaterialHandle<T, M>>,
+    custom_fill_query: Query<(), With<StatbarCustomFill<T>>>,
+) where
+    T: 'static,
+    M: StatbarMaterial,
+{
+    despawn_orphaned_fill_quads::<T, _>(
+        commands,
+        removed,
+        quad_query,
+        material_query,
+        custom_fill_query,
+        |material| material.quad,
+    );
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_statbar_materials<T, M>(
+    resources: FillQuadResources,
+    mut materials: ResMut<Assets<M>>,
+    material_query: Query<(FillQuadItem<T>, &StatbarMaterialHandle<T, M>), FillQuadFilter<T>>,
+    mut quad_query: Query<
+        (
+            &mut Handle<M>,
+            &mut Transform,
+            &mut GlobalTransform,
+            &mut Visibility,
+        ),
+        (
+            With<StatbarFillQuad<StatbarMaterialHandle<T, M>>>,
+            Without<Statbar<T>>,
+            Without<Camera>,
+        ),
+    >,
+) where
+    T: 'static,
+    M: StatbarMaterial,
+{
+    let context = resources.context();
+    material_query.for_each(|(item, material)| {
+        let (mut handle, mut transform, mut global_transform, mut visibility) =
+            match material.quad.map(|quad| quad_query.get_mut(quad)) {
+                Some(Ok(quad_components)) => quad_components,
+                _ => return,
+            };
+        if *handle != material.handle {
+            *handle = material.handle.clone();
+        }
+        let quad = fill_quad(item, &context);
+        if let Some((_, params)) = &quad {
+            if let Some(material) = materials.get_mut(&material.handle) {
+                material.set_statbar_params(params);
+            }
+        }
+        place_fill_quad(
+            quad.map(|(placement, _)| placement),
+            &mut transform,
+            &mut global_transform,
+            &mut visibility,
+        );
+    });
+}
@@ -0,0 +1,361 @@
+use crate::auto_hide::fade_color;
+use crate::*;
+use std::marker::PhantomData;
+
+/// Draws the Statbar on the same entity as a Bevy UI node instead of in the world.
+///
+/// The size and position of the bar are set by the node's `Style` and the flexbox layout,
+/// `Statbar::length`, `Statbar::thickness` and `Statbar::displacement` aren't used.
+/// The colors, orientation, value and observers of the `Statbar` work the same as for world space bars,
+/// as do flashes, pulses, shakes, auto hiding and the `StatbarColorblindMode`.
+/// A `StatbarBorder` is drawn as padding around the bar, with a child node for each side
+/// when the sides have different colors. Its kind isn't used, see `StatbarBorderKind`.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarNode<T>
+where
+    T: 'static,
+{
+    /// child nodes drawing the left, right, bottom and top sides of the border,
+    /// only shown when the sides have different colors
+    #[reflect(ignore)]
+    sides: Option<[Entity; 4]>,
+    /// child node drawn with the empty color
+    #[reflect(ignore)]
+    back: Option<Entity>,
+    /// child of the back node drawn with the full color
+    #[reflect(ignore)]
+    fill: Option<Entity>,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarNode<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self {
+            sides: None,
+            back: None,
+            fill: None,
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// A UI node with a statbar
+#[derive(Bundle)]
+pub struct StatbarNodeBundle<T>
+where
+    T: 'static,
+{
+    pub statbar: Statbar<T>,
+    pub statbar_node: StatbarNode<T>,
+    #[bundle]
+    pub node: NodeBundle,
+}
+
+impl<T> Default for StatbarNodeBundle<T>
+where
+    T: 'static,
+{
+    /// A 200 by 20 pixel statbar node
+    fn default() -> Self {
+        Self {
+            statbar: Default::default(),
+            statbar_node: Default::default(),
+            node: NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(200.), Val::Px(20.)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        }
+    }
+}
+
+pub(crate) fn spawn_statbar_node_children<T>(
+    mut commands: Commands,
+    mut node_query: Query<(Entity, &mut StatbarNode<T>), Added<StatbarNode<T>>>,
+) where
+    T: 'static,
+{
+    node_query.for_each_mut(|(id, mut statbar_node)| {
+        if statbar_node.back.is_some() {
+            return;
+        }
+        let mut side = || {
+            commands
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        display: Display::None,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .id()
+        };
+        let sides = [side(), side(), side(), side()];
+        let fill = commands
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .id();
+        let back = commands
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .add_child(fill)
+            .id();
+        commands.entity(id).push_children(&sides).add_child(back);
+        statbar_node.sides = Some(sides);
+        statbar_node.back = Some(back);
+        statbar_node.fill = Some(fill);
+    });
+}
+
+/// Sets the styles and colors of the nodes that draw each `StatbarNode`.
+///
+/// Runs every frame to animate effects and to style the child nodes on the frame after they're spawned,
+/// but only writes the styles and colors that have changed so the UI isn't laid out again every frame.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_statbar_nodes<T>(
+    colorblind_mode: Option<Res<StatbarColorblindMode>>,
+    mut node_query: Query<(
+        &Statbar<T>,
+        Option<&StatbarBorder<T>>,
+        &StatbarNode<T>,
+        Option<&StatbarAutoHide<T>>,
+        (
+            Option<&StatbarFlash<T>>,
+            Option<&StatbarPulse<T>>,
+            Option<&StatbarShake<T>>,
+        ),
+        &mut Style,
+        &mut UiColor,
+    )>,
+    mut child_query: Query<(&mut Style, &mut UiColor), Without<StatbarNode<T>>>,
+) where
+    T: 'static,
+{
+    let colorblind_mode = colorblind_mode.map(|mode| *mode).unwrap_or_default();
+    node_query.for_each_mut(
+        |(bar, border, statbar_node, auto_hide, (flash, pulse, shake), mut style, mut color)| {
+            let alpha = auto_hide.map(|auto_hide| auto_hide.alpha()).unwrap_or(1.);
+            let display = if bar.hide || alpha <= 0. {
+                Display::None
+            } else {
+                Display::Flex
+            };
+            if style.display != display {
+                style.display = display;
+            }
+            if display == Display::None {
+                return;
+            }
+            let paint = |color: Color| colorblind_mode.apply(fade_color(color, alpha));
+
+            let (low, high) = border
+                .map(|border| border.outer_extents())
+                .unwrap_or_default();
+            let padding = UiRect {
+                left: Val::Px(low.x),
+                right: Val::Px(high.x),
+                top: Val::Px(high.y),
+                bottom: Val::Px(low.y),
+            };
+            if style.padding != padding {
+                style.padding = padding;
+            }
+            // sides of different colors are drawn by the side nodes, otherwise the padding is drawn in the border color
+            let side_colors = border.and_then(|border| {
+                let side_colors = border.side_colors();
+                side_colors
+                    .iter()
+                    .any(|&side| side != border.color)
+                    .then_some(side_colors)
+            });
+            let border_color = match (border, side_colors) {
+                (Some(border), None) => paint(border.color),
+                _ => Color::NONE,
+            };
+            if color.0 != border_color {
+                color.0 = border_color;
+            }
+            for (index, side) in statbar_node.sides.into_iter().flatten().enumerate() {
+                if let Ok((mut side_style, mut side_color)) = child_query.get_mut(side) {
+                    style_border_side(
+                        index,
+                        side_colors.map(|side_colors| paint(side_colors[index])),
+                        low,
+                        high,
+                        &mut side_style,
+                        &mut side_color,
+                    );
+                }
+            }
+
+            if let Some(Ok((mut back_style, mut back_color))) =
+                statbar_node.back.map(|back| child_query.get_mut(back))
+            {
+                let empty_color = paint(bar.empty_color);
+                if back_color.0 != empty_color {
+                    back_color.0 = empty_color;
+                }
+                // shakes move the bar inside its node, so the layout around the node isn't disturbed
+                let offset = shake.map(|shake| shake.offset()).unwrap_or_default();
+                let position = UiRect {
+                    left: Val::Px(offset.x),
+                    top: Val::Px(-offset.y),
+                    ..Default::default()
+                };
+                if back_style.position != position {
+                    back_style.position = position;
+                }
+            }
+
+            if let Some(Ok((mut fill_style, mut fill_color))) =
+                statbar_node.fill.map(|fill| child_query.get_mut(fill))
+            {
+                let value = bar.value.clamp(0., 1.);
+                let mut full_color = bar.color;
+                if let Some(pulse) = pulse {
+                    full_color = pulse.apply(full_color, value);
+                }
+                if let Some(flash) = flash {
+                    full_color = flash.apply(full_color);
+                }
+                let full_color = paint(full_color);
+                if fill_color.0 != full_color {
+                    fill_color.0 = full_color;
+                }
+                let full = Val::Percent(100. * value);
+                let zero = Val::Px(0.);
+                // the fill is pinned to the side of the back node it starts filling from
+                let (size, position) = match (bar.vertical, bar.reverse) {
+                    (false, false) => (
+                        Size::new(full, Val::Percent(100.)),
+                        UiRect {
+                            left: zero,
+                            bottom: zero,
+                            ..Default::default()
+                        },
+                    ),
+                    (false, true) => (
+                        Size::new(full, Val::Percent(100.)),
+                        UiRect {
+                            right: zero,
+                            bottom: zero,
+                            ..Default::default()
+                        },
+                    ),
+                    (true, false) => (
+                        Size::new(Val::Percent(100.), full),
+                        UiRect {
+                            left: zero,
+                            bottom: zero,
+                            ..Default::default()
+                        },
+                    ),
+                    (true, true) => (
+                        Size::new(Val::Percent(100.), full),
+                        UiRect {
+                            left: zero,
+                            top: zero,
+                            ..Default::default()
+                        },
+                    ),
+                };
+                if fill_style.size != size {
+                    fill_style.size = size;
+                }
+                if fill_style.position != position {
+                    fill_style.position = position;
+                }
+            }
+        },
+    );
+}
+
+/// Styles the node drawing the left, right, bottom or top side of a border, hidden without a `color`.
+/// The left and right sides cover the corners, like the sides of the border sprites.
+fn style_border_side(
+    index: usize,
+    color: Option<Color>,
+    low: Vec2,
+    high: Vec2,
+    style: &mut Style,
+    ui_color: &mut UiColor,
+) {
+    let color = match color {
+        Some(color) => color,
+        None => {
+            if style.display != Display::None {
+                style.display = Display::None;
+            }
+            return;
+        }
+    };
+    if style.display != Display::Flex {
+        style.display = Display::Flex;
+    }
+    if ui_color.0 != color {
+        ui_color.0 = color;
+    }
+    let zero = Val::Px(0.);
+    let (size, position) = match index {
+        0 => (
+            Size::new(Val::Px(low.x), Val::Auto),
+            UiRect {
+                left: zero,
+                top: zero,
+                bottom: zero,
+                ..Default::default()
+            },
+        ),
+        1 => (
+            Size::new(Val::Px(high.x), Val::Auto),
+            UiRect {
+                right: zero,
+                top: zero,
+                bottom: zero,
+                ..Default::default()
+            },
+        ),
+        2 => (
+            Size::new(Val::Auto, Val::Px(low.y)),
+            UiRect {
+                left: Val::Px(low.x),
+                right: Val::Px(high.x),
+                bottom: zero,
+                ..Default::default()
+            },
+        ),
+        _ => (
+            Size::new(Val::Auto, Val::Px(high.y)),
+            UiRect {
+                left: Val::Px(low.x),
+                right: Val::Px(high.x),
+                top: zero,
+                ..Default::default()
+            },
+        ),
+    };
+    if style.size != size {
+        style.size = size;
+    }
+    if style.position != position {
+        style.position = position;
+    }
+}
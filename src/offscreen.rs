@@ -0,0 +1,122 @@
+use crate::map_camera_entity;
+use bevy::ecs::entity::EntityMap;
+use bevy::ecs::entity::MapEntities;
+use bevy::ecs::entity::MapEntitiesError;
+use bevy::ecs::reflect::ReflectMapEntities;
+use bevy::prelude::*;
+#[cfg(feature = "serialize")]
+use serde::Deserialize;
+#[cfg(feature = "serialize")]
+use serde::Serialize;
+use std::marker::PhantomData;
+
+/// Keeps the corresponding Statbar on screen when its entity leaves the camera's viewport.
+///
+/// Instead of disappearing, the bar is clamped to the edge of the viewport.
+/// Could be used for the health bars of bosses or allies, so the player always knows their state.
+///
+/// Statbar sprites are extracted once and drawn by every camera, so a bar can only be clamped
+/// to a single viewport. With split screen or a minimap the bar is drawn at the same clamped position
+/// in every view, so set the camera it's clamped to with `with_camera`, otherwise the active camera
+/// with the highest priority is used, which can change as cameras are activated.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component, MapEntities)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
+pub struct StatbarEdgeClamp<T>
+where
+    T: 'static,
+{
+    /// distance in logical pixels between the clamped bar and the edge of the viewport
+    pub margin: f32,
+    /// length of the arms of the chevron drawn next to the clamped bar, pointing toward its entity
+    /// * 0.0 => no arrow
+    pub arrow_size: f32,
+    /// color of the arrow
+    pub arrow_color: Color,
+    /// camera whose viewport the bar is clamped to, in every view
+    /// * None => the active camera with the highest priority
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    pub camera: Option<Entity>,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarEdgeClamp<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(8.)
+    }
+}
+
+impl<T> MapEntities for StatbarEdgeClamp<T>
+where
+    T: 'static,
+{
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        map_camera_entity(&mut self.camera, entity_map);
+        Ok(())
+    }
+}
+
+impl<T> StatbarEdgeClamp<T>
+where
+    T: 'static,
+{
+    /// Clamp the bar `margin` logical pixels inside the viewport, without an arrow
+    pub fn new(margin: f32) -> Self {
+        Self {
+            margin,
+            arrow_size: 0.,
+            arrow_color: Color::WHITE,
+            camera: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Draw a chevron arrow pointing toward the off-screen entity
+    pub fn with_arrow(mut self, size: f32, color: Color) -> Self {
+        self.arrow_size = size;
+        self.arrow_color = color;
+        self
+    }
+
+    /// Clamp to the viewport of `camera`, instead of the highest priority camera
+    pub fn with_camera(mut self, camera: Entity) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+}
+
+/// If a bar centered on `position` with `half_extents` isn't entirely
+/// inside the camera's viewport shrunk by `margin`, returns the clamped position
+/// of the bar and the direction from the clamped position to `position`.
+pub(crate) fn clamp_to_viewport(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    position: Vec3,
+    half_extents: Vec2,
+    margin: f32,
+) -> Option<(Vec2, Vec2)> {
+    let viewport_size = camera.logical_viewport_size()?;
+    let world_to_ndc = camera.projection_matrix() * camera_transform.compute_matrix().inverse();
+    let ndc = world_to_ndc.project_point3(position);
+    let ndc_half_extents = world_to_ndc
+        .transform_vector3(half_extents.extend(0.))
+        .truncate()
+        .abs();
+    let bounds = (Vec2::ONE - 2. * margin / viewport_size - ndc_half_extents).max(Vec2::ZERO);
+    let clamped = ndc.truncate().clamp(-bounds, bounds);
+    if clamped == ndc.truncate() {
+        return None;
+    }
+    let clamped_position = world_to_ndc
+        .inverse()
+        .project_point3(clamped.extend(ndc.z))
+        .truncate();
+    let direction = (position.truncate() - clamped_position).normalize_or_zero();
+    Some((clamped_position, direction))
+}
@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+#[cfg(feature = "serialize")]
+use serde::Deserialize;
+#[cfg(feature = "serialize")]
+use serde::Serialize;
+
+/// A kind of color blindness
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum ColorVisionDeficiency {
+    /// no red cones
+    Protanopia,
+    /// no green cones, the most common
+    Deuteranopia,
+    /// no blue cones
+    Tritanopia,
+}
+
+impl ColorVisionDeficiency {
+    /// Machado et al. 2009 simulation matrix for linear RGB, at full severity
+    fn simulation_matrix(self) -> Mat3 {
+        let rows = match self {
+            Self::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Self::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Self::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        };
+        Mat3::from_cols_array_2d(&rows).transpose()
+    }
+
+    /// Shows a color the way it's seen with this deficiency
+    pub fn simulate(self, color: Color) -> Color {
+        let [r, g, b, a] = color.as_linear_rgba_f32();
+        let c = self.simulation_matrix() * Vec3::new(r, g, b);
+        Color::rgba_linear(c.x, c.y, c.z, a)
+    }
+
+    /// Shifts the colors that can't be told apart with this deficiency
+    /// into colors that can, by daltonization
+    pub fn daltonize(self, color: Color) -> Color {
+        let [r, g, b, a] = color.as_linear_rgba_f32();
+        let original = Vec3::new(r, g, b);
+        let error = original - self.simulation_matrix() * original;
+        let shift = match self {
+            Self::Protanopia | Self::Deuteranopia => {
+                Vec3::new(0., 0.7 * error.x + error.y, 0.7 * error.x + error.z)
+            }
+            Self::Tritanopia => Vec3::new(error.x + 0.7 * error.z, error.y + 0.7 * error.z, 0.),
+        };
+        let c = (original + shift).clamp(Vec3::ZERO, Vec3::ONE);
+        Color::rgba_linear(c.x, c.y, c.z, a)
+    }
+}
+
+/// Changes the colors of every statbar when it is drawn.
+///
+/// Insert as a resource to preview the game with a color vision deficiency,
+/// or to remap the colors for players who have one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum StatbarColorblindMode {
+    /// colors are drawn unchanged
+    #[default]
+    Off,
+    /// colors are drawn as they are seen with the deficiency
+    Simulate(ColorVisionDeficiency),
+    /// colors are remapped to be easier to tell apart with the deficiency
+    Daltonize(ColorVisionDeficiency),
+}
+
+impl StatbarColorblindMode {
+    pub fn apply(self, color: Color) -> Color {
+        match self {
+            Self::Off => color,
+            Self::Simulate(deficiency) => deficiency.simulate(color),
+            Self::Daltonize(deficiency) => deficiency.daltonize(color),
+        }
+    }
+}
+
+/// Colors for a statbar that changes color with its value
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct StatbarPalette {
+    /// color when the value is low
+    pub low: Color,
+    /// color when the value is high
+    pub high: Color,
+    /// color of the empty part of the bar
+    pub empty: Color,
+}
+
+impl StatbarPalette {
+    /// the colors of `StatbarColorSwitch::default()`, hard to tell apart with protanopia or deuteranopia
+    pub const RED_GREEN: Self = Self {
+        low: Color::rgb(1.0, 0.0, 0.0),
+        high: Color::rgb(0.0, 1.0, 0.0),
+        empty: Color::rgb(0.2, 0.2, 0.2),
+    };
+
+    /// orange and blue from the Okabe-Ito palette, safe for protanopia and deuteranopia
+    pub const ORANGE_BLUE: Self = Self {
+        low: Color::rgb(0.902, 0.624, 0.0),
+        high: Color::rgb(0.0, 0.447, 0.698),
+        empty: Color::rgb(0.2, 0.2, 0.2),
+    };
+
+    /// vermillion and bluish green from the Okabe-Ito palette, safe for tritanopia
+    pub const VERMILLION_GREEN: Self = Self {
+        low: Color::rgb(0.835, 0.369, 0.0),
+        high: Color::rgb(0.0, 0.62, 0.451),
+        empty: Color::rgb(0.2, 0.2, 0.2),
+    };
+
+    /// bright colors on a black background, distinguishable by lightness alone
+    pub const HIGH_CONTRAST: Self = Self {
+        low: Color::rgb(1.0, 1.0, 1.0),
+        high: Color::rgb(1.0, 0.85, 0.0),
+        empty: Color::rgb(0.0, 0.0, 0.0),
+    };
+
+    /// A palette that's easy to read with the deficiency
+    pub fn for_deficiency(deficiency: ColorVisionDeficiency) -> Self {
+        match deficiency {
+            ColorVisionDeficiency::Protanopia | ColorVisionDeficiency::Deuteranopia => {
+                Self::ORANGE_BLUE
+            }
+            ColorVisionDeficiency::Tritanopia => Self::VERMILLION_GREEN,
+        }
+    }
+}
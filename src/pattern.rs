@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::render::render_resource::AddressMode;
+use bevy::render::render_resource::Extent3d;
+use bevy::render::render_resource::SamplerDescriptor;
+use bevy::render::render_resource::TextureDimension;
+use bevy::render::render_resource::TextureFormat;
+use bevy::render::texture::ImageSampler;
+use serde::Deserialize;
+use serde::Serialize;
+use std::marker::PhantomData;
+
+/// Width and height in pixels of the pattern images
+const PATTERN_SIZE: u32 = 8;
+
+const STRIPES_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Image::TYPE_UUID, 2741097311518733401);
+const DOTS_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Image::TYPE_UUID, 2741097311518733402);
+const CROSSHATCH_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Image::TYPE_UUID, 2741097311518733403);
+
+/// A pattern drawn over the full part of a statbar
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect_value(PartialEq, Serialize, Deserialize)]
+pub enum StatbarPatternKind {
+    /// diagonal stripes
+    Stripes,
+    Dots,
+    /// diagonal stripes in both directions
+    Crosshatch,
+}
+
+impl StatbarPatternKind {
+    pub(crate) fn image(self) -> HandleUntyped {
+        match self {
+            Self::Stripes => STRIPES_HANDLE,
+            Self::Dots => DOTS_HANDLE,
+            Self::Crosshatch => CROSSHATCH_HANDLE,
+        }
+    }
+
+    fn covers(self, x: u32, y: u32) -> bool {
+        match self {
+            Self::Stripes => (x + y) % PATTERN_SIZE < 3,
+            Self::Dots => {
+                let d = Vec2::new(x as f32 - 3.5, y as f32 - 3.5);
+                d.length_squared() <= 4.
+            }
+            Self::Crosshatch => {
+                (x + y) % PATTERN_SIZE < 2 || (x + PATTERN_SIZE - y) % PATTERN_SIZE < 2
+            }
+        }
+    }
+}
+
+/// Draws a pattern over the full part of the corresponding Statbar,
+/// so its state isn't shown by color alone.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
+pub struct StatbarPattern<T>
+where
+    T: 'static,
+{
+    pub kind: StatbarPatternKind,
+    pub color: Color,
+    /// size in world units of each pixel of the pattern
+    pub scale: f32,
+    /// the pattern is only drawn while the bar's value is less than or equal to this threshold,
+    /// 1.0 => always drawn
+    pub threshold: f32,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarPattern<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(StatbarPatternKind::Stripes)
+    }
+}
+
+impl<T> StatbarPattern<T>
+where
+    T: 'static,
+{
+    /// A translucent black pattern that is always drawn
+    pub fn new(kind: StatbarPatternKind) -> Self {
+        Self {
+            kind,
+            color: Color::rgba(0., 0., 0., 0.5),
+            scale: 1.,
+            threshold: 1.,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Only draw the pattern when the bar's value is less than or equal to `threshold`
+    pub fn below(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+}
+
+/// Creates the repeating pattern images, white where the pattern is drawn
+pub(crate) fn add_pattern_images(images: &mut Assets<Image>) {
+    for kind in [
+        StatbarPatternKind::Stripes,
+        StatbarPatternKind::Dots,
+        StatbarPatternKind::Crosshatch,
+    ] {
+        let mut data = Vec::with_capacity((4 * PATTERN_SIZE * PATTERN_SIZE) as usize);
+        for y in 0..PATTERN_SIZE {
+            for x in 0..PATTERN_SIZE {
+                let alpha = if kind.covers(x, y) { 255 } else { 0 };
+                data.extend_from_slice(&[255, 255, 255, alpha]);
+            }
+        }
+        let mut image = Image::new(
+            Extent3d {
+                width: PATTERN_SIZE,
+                height: PATTERN_SIZE,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        image.sampler_descriptor = ImageSampler::Descriptor(SamplerDescriptor {
+            address_mode_u: AddressMode::Repeat,
+            address_mode_v: AddressMode::Repeat,
+            ..ImageSampler::nearest_descriptor()
+        });
+        images.set_untracked(kind.image(), image);
+    }
+}
@@ -0,0 +1,231 @@
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::render::render_resource::Extent3d;
+use bevy::render::render_resource::TextureDimension;
+use bevy::render::render_resource::TextureFormat;
+use bevy::render::texture::ImageSampler;
+use bevy::sprite::Rect;
+#[cfg(feature = "serialize")]
+use serde::Deserialize;
+#[cfg(feature = "serialize")]
+use serde::Serialize;
+use std::marker::PhantomData;
+
+/// Width and height in pixels of the soft edge image
+const SOFT_EDGE_SIZE: u32 = 32;
+
+pub(crate) const SOFT_EDGE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Image::TYPE_UUID, 2741097311518733410);
+
+/// Draws a soft shadow behind the corresponding Statbar and its border
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
+pub struct StatbarShadow<T>
+where
+    T: 'static,
+{
+    /// displacement of the shadow from the bar
+    pub offset: Vec2,
+    /// distance over which the edges of the shadow fade out
+    pub blur: f32,
+    pub color: Color,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarShadow<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(Vec2::new(2., -2.), 4.)
+    }
+}
+
+impl<T> StatbarShadow<T>
+where
+    T: 'static,
+{
+    /// A translucent black shadow
+    pub fn new(offset: Vec2, blur: f32) -> Self {
+        Self {
+            offset,
+            blur,
+            color: Color::rgba(0., 0., 0., 0.5),
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+/// Draws a soft glow around the corresponding Statbar and its border,
+/// that fades in as the value of the statbar rises.
+///
+/// Glows only when the bar is full by default, like an ultimate ability meter.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
+pub struct StatbarGlow<T>
+where
+    T: 'static,
+{
+    pub color: Color,
+    /// distance the glow extends past the edges of the bar
+    pub radius: f32,
+    /// value where the glow starts to fade in
+    pub start: f32,
+    /// value where the glow is at full strength,
+    /// if `full <= start` the glow switches on at `full` without fading
+    pub full: f32,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarGlow<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(Color::rgba(1., 1., 0.6, 0.8), 6.)
+    }
+}
+
+impl<T> StatbarGlow<T>
+where
+    T: 'static,
+{
+    /// A glow that is drawn when the statbar is full
+    pub fn new(color: Color, radius: f32) -> Self {
+        Self {
+            color,
+            radius,
+            start: 1.,
+            full: 1.,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Fade the glow in as the value rises from `start` to `full`
+    pub fn fade_in(mut self, start: f32, full: f32) -> Self {
+        self.start = start;
+        self.full = full;
+        self
+    }
+
+    /// Strength of the glow for a statbar with this value, from 0.0 to 1.0
+    pub fn intensity(&self, value: f32) -> f32 {
+        if self.full <= self.start {
+            if self.full <= value {
+                1.
+            } else {
+                0.
+            }
+        } else {
+            ((value - self.start) / (self.full - self.start)).clamp(0., 1.)
+        }
+    }
+}
+
+/// One of the nine slices of a soft edged rectangle,
+/// positioned relative to the center of the rectangle
+pub(crate) struct SoftQuad {
+    pub offset: Vec2,
+    pub size: Vec2,
+    /// region of the soft edge image, None => solid
+    pub rect: Option<Rect>,
+}
+
+/// The slices of a rectangle of `size` with edges that fade out over `2 * blur`,
+/// centered on the edges of the rectangle, `blur.x` on the left and right and `blur.y` on the top and bottom.
+///
+/// The corners and sides are cut from the soft edge image, so the fade is
+/// the same width on every side however the rectangle is stretched.
+pub(crate) fn soft_rect_quads(size: Vec2, blur: Vec2) -> Vec<SoftQuad> {
+    let blur = blur
+        .min(0.5 * Vec2::splat(size.min_element()))
+        .max(Vec2::ZERO);
+    if blur.x <= 0. && blur.y <= 0. {
+        return vec![SoftQuad {
+            offset: Vec2::ZERO,
+            size,
+            rect: None,
+        }];
+    }
+    let n = SOFT_EDGE_SIZE as f32;
+    let half = 0.5 * n;
+    let inner = size - 2. * blur;
+    let edge = 0.5 * size;
+    // texture rows run from top to bottom
+    let columns = [(0., half), (half - 1., half), (half, n)];
+    let rows = [(half, n), (half - 1., half), (0., half)];
+    let mut quads = Vec::with_capacity(9);
+    for (j, &(row_min, row_max)) in rows.iter().enumerate() {
+        for (i, &(column_min, column_max)) in columns.iter().enumerate() {
+            let (x, width) = match i {
+                0 => (-edge.x, 2. * blur.x),
+                1 => (0., inner.x),
+                _ => (edge.x, 2. * blur.x),
+            };
+            let (y, height) = match j {
+                0 => (-edge.y, 2. * blur.y),
+                1 => (0., inner.y),
+                _ => (edge.y, 2. * blur.y),
+            };
+            if width <= 0. || height <= 0. {
+                continue;
+            }
+            let rect = if i == 1 && j == 1 {
+                None
+            } else {
+                Some(Rect {
+                    min: Vec2::new(column_min, row_min),
+                    max: Vec2::new(column_max, row_max),
+                })
+            };
+            quads.push(SoftQuad {
+                offset: Vec2::new(x, y),
+                size: Vec2::new(width, height),
+                rect,
+            });
+        }
+    }
+    quads
+}
+
+/// Creates the soft edge image, opaque in the middle and fading out smoothly to each edge
+pub(crate) fn add_soft_edge_image(images: &mut Assets<Image>) {
+    let half = 0.5 * SOFT_EDGE_SIZE as f32;
+    let fade = |i: u32| {
+        let t = ((i as f32 + 0.5).min(SOFT_EDGE_SIZE as f32 - i as f32 - 0.5) / (half - 1.))
+            .clamp(0., 1.);
+        t * t * (3. - 2. * t)
+    };
+    let mut data = Vec::with_capacity((4 * SOFT_EDGE_SIZE * SOFT_EDGE_SIZE) as usize);
+    for y in 0..SOFT_EDGE_SIZE {
+        for x in 0..SOFT_EDGE_SIZE {
+            let alpha = (255. * fade(x) * fade(y)).round() as u8;
+            data.extend_from_slice(&[255, 255, 255, alpha]);
+        }
+    }
+    let mut image = Image::new(
+        Extent3d {
+            width: SOFT_EDGE_SIZE,
+            height: SOFT_EDGE_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    image.sampler_descriptor = ImageSampler::linear();
+    images.set_untracked(SOFT_EDGE_HANDLE, image);
+}
@@ -0,0 +1,274 @@
+use crate::declutter::DeclutterResult;
+use crate::declutter::StatbarDeclutterState;
+use crate::extraction::default_camera;
+use crate::extraction::statbar_outer_offset;
+use crate::extraction::statbar_outer_size;
+use crate::*;
+use bevy::ecs::entity::EntityMap;
+use bevy::ecs::entity::MapEntities;
+use bevy::ecs::entity::MapEntitiesError;
+use bevy::ecs::reflect::ReflectMapEntities;
+use bevy::render::camera::RenderTarget;
+use std::marker::PhantomData;
+
+/// Turns the corresponding Statbar into an input.
+/// Pressing the mouse button over the bar, or dragging along it, sets its value.
+///
+/// Sliders on standalone statbars set `Statbar::value` directly.
+/// Sliders on statbars that observe a component or resource write the new value back to it
+/// through `StatbarSettable`, register them with
+/// `add_statbar_component_slider` or `add_statbar_resource_slider`.
+///
+/// The hit test uses the position of the bar before any shake or edge clamping,
+/// edge clamped bars can't be dragged while clamped.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component, MapEntities)]
+pub struct StatbarSlider<T>
+where
+    T: 'static,
+{
+    /// mouse button that drags the slider
+    #[reflect(ignore)]
+    pub button: MouseButton,
+    /// values are rounded to multiples of the step, 0.0 => no rounding
+    pub step: f32,
+    /// camera used to find the cursor's position in the world,
+    /// None => the active camera with the highest priority
+    pub camera: Option<Entity>,
+    /// true while the slider is being dragged
+    dragging: bool,
+    /// value set by the slider that hasn't been written back to the observed stat
+    #[reflect(ignore)]
+    pending: Option<f32>,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarSlider<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(0.)
+    }
+}
+
+impl<T> MapEntities for StatbarSlider<T>
+where
+    T: 'static,
+{
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        map_camera_entity(&mut self.camera, entity_map);
+        Ok(())
+    }
+}
+
+impl<T> StatbarSlider<T>
+where
+    T: 'static,
+{
+    /// A slider dragged with the left mouse button
+    pub fn new(step: f32) -> Self {
+        Self {
+            button: MouseButton::Left,
+            step,
+            camera: None,
+            dragging: false,
+            pending: None,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn with_button(mut self, button: MouseButton) -> Self {
+        self.button = button;
+        self
+    }
+
+    pub fn with_camera(mut self, camera: Entity) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+
+    /// true while the slider is being dragged
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+}
+
+/// Position of the mouse cursor in world space, as seen by `camera`
+pub(crate) fn cursor_world_position(
+    windows: &Windows,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec2> {
+    let window = match camera.target {
+        RenderTarget::Window(window_id) => windows.get(window_id)?,
+        _ => return None,
+    };
+    // window cursor positions start from the bottom left, viewports from the top left
+    let cursor = window.cursor_position()?;
+    let cursor = Vec2::new(cursor.x, window.height() - cursor.y);
+    let (min, max) = camera.logical_viewport_rect()?;
+    let relative = (cursor - min) / (max - min);
+    if !(0. ..=1.).contains(&relative.x) || !(0. ..=1.).contains(&relative.y) {
+        return None;
+    }
+    let ndc = Vec3::new(2. * relative.x - 1., 1. - 2. * relative.y, 0.);
+    let ndc_to_world = camera_transform.compute_matrix() * camera.projection_matrix().inverse();
+    Some(ndc_to_world.project_point3(ndc).truncate())
+}
+
+/// Position of the center of a statbar, the same as where it's drawn before effects and edge clamping
+pub(crate) fn statbar_center<V>(
+    entity: Entity,
+    bar: &Statbar<V>,
+    global_transform: &GlobalTransform,
+    declutter_state: Option<&StatbarDeclutterState>,
+) -> Option<Vec2>
+where
+    V: 'static,
+{
+    let center = global_transform.translation().truncate() + bar.displacement;
+    match declutter_state.and_then(|declutter_state| declutter_state.get::<V>(entity)) {
+        Some(DeclutterResult::Culled) => None,
+        Some(DeclutterResult::Moved(offset)) => Some(center + offset),
+        None => Some(center),
+    }
+}
+
+/// True if `point` is on the statbar or its border
+pub(crate) fn statbar_contains<V>(
+    bar: &Statbar<V>,
+    border: Option<&StatbarBorder<V>>,
+    center: Vec2,
+    point: Vec2,
+) -> bool {
+    let half_extents = 0.5 * statbar_outer_size(bar, border);
+    let local = (point - center - statbar_outer_offset(border)).abs();
+    local.x <= half_extents.x && local.y <= half_extents.y
+}
+
+/// The value of a statbar filled up to `point`
+fn statbar_value_at<V>(bar: &Statbar<V>, center: Vec2, point: Vec2) -> f32 {
+    if bar.length <= 0. {
+        return bar.value;
+    }
+    let major_axis = if bar.vertical { Vec2::Y } else { Vec2::X };
+    let direction = if bar.reverse { -1. } else { 1. };
+    (direction * (point - center).dot(major_axis) / bar.length + 0.5).clamp(0., 1.)
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_statbar_sliders<T>(
+    mouse_buttons: Option<Res<Input<MouseButton>>>,
+    windows: Option<Res<Windows>>,
+    declutter_state: Option<Res<StatbarDeclutterState>>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform)>,
+    mut slider_query: Query<(
+        Entity,
+        &mut Statbar<T>,
+        &mut StatbarSlider<T>,
+        Option<&StatbarBorder<T>>,
+        Option<&StatbarAutoHide<T>>,
+        &GlobalTransform,
+        &ComputedVisibility,
+    )>,
+) where
+    T: 'static,
+{
+    let (mouse_buttons, windows) = match (mouse_buttons, windows) {
+        (Some(mouse_buttons), Some(windows)) => (mouse_buttons, windows),
+        _ => return,
+    };
+    let default_camera = default_camera(cameras.iter());
+    slider_query.for_each_mut(
+        |(id, mut bar, mut slider, border, auto_hide, global_transform, computed_visibility)| {
+            if !mouse_buttons.pressed(slider.button) {
+                if slider.dragging {
+                    slider.dragging = false;
+                }
+                return;
+            }
+            let camera = match slider.camera {
+                Some(camera_id) => cameras.get(camera_id).ok(),
+                None => default_camera,
+            };
+            let cursor = match camera.and_then(|(_, camera, camera_transform)| {
+                cursor_world_position(&windows, camera, camera_transform)
+            }) {
+                Some(cursor) => cursor,
+                None => return,
+            };
+            let center =
+                match statbar_center(id, &bar, global_transform, declutter_state.as_deref()) {
+                    Some(center) => center,
+                    None => return,
+                };
+            if mouse_buttons.just_pressed(slider.button) {
+                let alpha = auto_hide.map(|auto_hide| auto_hide.alpha()).unwrap_or(1.);
+                let is_visible = !bar.hide && 0. < alpha && computed_visibility.is_visible();
+                if is_visible && statbar_contains(&bar, border, center, cursor) {
+                    slider.dragging = true;
+                }
+            }
+            if !slider.dragging {
+                return;
+            }
+            let mut value = statbar_value_at(&bar, center, cursor);
+            if 0. < slider.step {
+                value = ((value / slider.step).round() * slider.step).clamp(0., 1.);
+            }
+            if bar.value != value {
+                bar.value = value;
+                slider.pending = Some(value);
+            }
+        },
+    );
+}
+
+/// Writes the values set by sliders back to the components the statbars observe
+#[allow(clippy::type_complexity)]
+pub(crate) fn write_statbar_slider_values<T>(
+    mut slider_query: Query<(
+        Entity,
+        &mut StatbarSlider<T>,
+        Option<&Parent>,
+        Option<&StatbarObserveParent>,
+        Option<&StatbarObserveEntity>,
+    )>,
+    mut value_query: Query<&mut T>,
+) where
+    T: StatbarSettable + Component,
+{
+    slider_query.for_each_mut(|(id, mut slider, parent, observe_parent, observe_entity)| {
+        let value = match slider.pending {
+            Some(value) => value,
+            None => return,
+        };
+        slider.pending = None;
+        let target = match (parent, observe_parent, observe_entity) {
+            (Some(parent), Some(_), _) => parent.get(),
+            (None, Some(_), _) => return,
+            (_, None, Some(&StatbarObserveEntity(target))) => target,
+            (_, None, None) => id,
+        };
+        if let Ok(mut observed) = value_query.get_mut(target) {
+            observed.set_statbar_value(value);
+        }
+    });
+}
+
+/// Writes the values set by sliders back to the resource the statbars observe
+pub(crate) fn write_statbar_slider_resource_values<T>(
+    mut resource: ResMut<T>,
+    mut slider_query: Query<&mut StatbarSlider<T>>,
+) where
+    T: StatbarSettable + 'static + Send + Sync,
+{
+    slider_query.for_each_mut(|mut slider| {
+        if let Some(value) = slider.pending {
+            slider.pending = None;
+            resource.set_statbar_value(value);
+        }
+    });
+}
@@ -0,0 +1,284 @@
+use crate::*;
+use bevy::asset::AssetLoader;
+use bevy::asset::BoxedFuture;
+use bevy::asset::LoadContext;
+use bevy::asset::LoadedAsset;
+use bevy::ecs::event::ManualEventReader;
+use bevy::reflect::TypeUuid;
+use bevy::utils::HashSet;
+use serde::Deserialize;
+use serde::Serialize;
+use std::marker::PhantomData;
+
+/// The border of a `StatbarStyle`
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct StatbarStyleBorder {
+    pub color: Color,
+    pub left: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub top: f32,
+}
+
+/// The `StatbarColorLerp` of a `StatbarStyle`
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct StatbarStyleColorLerp {
+    pub min: Color,
+    pub max: Color,
+}
+
+/// The `StatbarColorSwitch` of a `StatbarStyle`
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct StatbarStyleColorSwitch {
+    pub pivot: f32,
+    pub low: Color,
+    pub high: Color,
+}
+
+/// A preset for the appearance of statbars, loaded from `.statbar.ron` files.
+///
+/// Every field is optional, fields that are left out don't change the statbar.
+/// ```ron
+/// (
+///     empty_color: Some(Rgba(red: 0.1, green: 0.0, blue: 0.1, alpha: 1.0)),
+///     length: Some(100.0),
+///     thickness: Some(16.0),
+///     border: Some((
+///         color: Rgba(red: 0.25, green: 0.25, blue: 0.25, alpha: 1.0),
+///         left: 2.0, right: 2.0, bottom: 2.0, top: 2.0,
+///     )),
+/// )
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize, TypeUuid)]
+#[uuid = "8a3c1d0e-6b4f-4f7a-9d2e-5c8b7a61f0d3"]
+#[serde(default)]
+pub struct StatbarStyle {
+    pub color: Option<Color>,
+    pub empty_color: Option<Color>,
+    pub length: Option<f32>,
+    pub thickness: Option<f32>,
+    pub displacement: Option<Vec2>,
+    pub vertical: Option<bool>,
+    pub reverse: Option<bool>,
+    /// inserts the `StatbarBorder` or sets its color and thickness,
+    /// an existing border keeps its kind and side colors
+    pub border: Option<StatbarStyleBorder>,
+    /// inserts or replaces the `StatbarColorLerp`
+    pub color_lerp: Option<StatbarStyleColorLerp>,
+    /// inserts or replaces the `StatbarColorSwitch`
+    pub color_switch: Option<StatbarStyleColorSwitch>,
+}
+
+impl StatbarStyle {
+    /// A copy of this style with every field that is set in `other` replaced
+    pub fn overridden_by(&self, other: &StatbarStyle) -> StatbarStyle {
+        StatbarStyle {
+            color: other.color.or(self.color),
+            empty_color: other.empty_color.or(self.empty_color),
+            length: other.length.or(self.length),
+            thickness: other.thickness.or(self.thickness),
+            displacement: other.displacement.or(self.displacement),
+            vertical: other.vertical.or(self.vertical),
+            reverse: other.reverse.or(self.reverse),
+            border: other.border.or(self.border),
+            color_lerp: other.color_lerp.or(self.color_lerp),
+            color_switch: other.color_switch.or(self.color_switch),
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct StatbarStyleLoader;
+
+impl AssetLoader for StatbarStyleLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let style: StatbarStyle = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(style));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["statbar.ron"]
+    }
+}
+
+/// Sets the corresponding Statbar, and its border and color components, from a `StatbarStyle` asset.
+///
+/// The style is applied again whenever the asset is modified,
+/// so with `AssetServerSettings::watch_for_changes` enabled, edits to the file show up while the app is running.
+#[derive(Clone, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarStyleHandle<T>
+where
+    T: 'static,
+{
+    pub handle: Handle<StatbarStyle>,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarStyleHandle<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl<T> StatbarStyleHandle<T>
+where
+    T: 'static,
+{
+    pub fn new(handle: Handle<StatbarStyle>) -> Self {
+        Self {
+            handle,
+            phantom: PhantomData,
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_statbar_styles<T>(
+    mut commands: Commands,
+    mut event_reader: Local<ManualEventReader<AssetEvent<StatbarStyle>>>,
+    events: Option<Res<Events<AssetEvent<StatbarStyle>>>>,
+    styles: Option<Res<Assets<StatbarStyle>>>,
+    mut style_query: Query<(
+        Entity,
+        &StatbarStyleHandle<T>,
+        ChangeTrackers<StatbarStyleHandle<T>>,
+        &mut Statbar<T>,
+        Option<&mut StatbarBorder<T>>,
+        Option<&mut StatbarColorLerp<T>>,
+        Option<&mut StatbarColorSwitch<T>>,
+    )>,
+) where
+    T: 'static,
+{
+    let (events, styles) = match (events, styles) {
+        (Some(events), Some(styles)) => (events, styles),
+        _ => return,
+    };
+    let loaded: HashSet<Handle<StatbarStyle>> = event_reader
+        .iter(&events)
+        .filter_map(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                Some(handle.clone_weak())
+            }
+            AssetEvent::Removed { .. } => None,
+        })
+        .collect();
+    style_query.for_each_mut(
+        |(id, style_handle, style_tracker, mut bar, border, color_lerp, color_switch)| {
+            if !style_tracker.is_changed() && !loaded.contains(&style_handle.handle) {
+                return;
+            }
+            let style = match styles.get(&style_handle.handle) {
+                Some(style) => style,
+                None => return,
+            };
+            apply_style(
+                &mut commands,
+                id,
+                style,
+                &mut bar,
+                border,
+                color_lerp,
+                color_switch,
+            );
+        },
+    );
+}
+
+/// Sets a statbar and its border and color components from a style,
+/// inserting the components that the style has but the statbar doesn't.
+pub(crate) fn apply_style<T>(
+    commands: &mut Commands,
+    id: Entity,
+    style: &StatbarStyle,
+    bar: &mut Statbar<T>,
+    border: Option<Mut<StatbarBorder<T>>>,
+    color_lerp: Option<Mut<StatbarColorLerp<T>>>,
+    color_switch: Option<Mut<StatbarColorSwitch<T>>>,
+) where
+    T: 'static,
+{
+    if let Some(color) = style.color {
+        bar.color = color;
+    }
+    if let Some(empty_color) = style.empty_color {
+        bar.empty_color = empty_color;
+    }
+    if let Some(length) = style.length {
+        bar.length = length;
+    }
+    if let Some(thickness) = style.thickness {
+        bar.thickness = thickness;
+    }
+    if let Some(displacement) = style.displacement {
+        bar.displacement = displacement;
+    }
+    if let Some(vertical) = style.vertical {
+        bar.vertical = vertical;
+    }
+    if let Some(reverse) = style.reverse {
+        bar.reverse = reverse;
+    }
+    if let Some(style_border) = style.border {
+        match border {
+            // styles don't set the kind or side colors of a border, so they're kept
+            Some(mut border) => {
+                border.color = style_border.color;
+                border.left = style_border.left;
+                border.right = style_border.right;
+                border.bottom = style_border.bottom;
+                border.top = style_border.top;
+            }
+            None => {
+                commands.entity(id).insert(StatbarBorder::<T>::new(
+                    style_border.color,
+                    style_border.left,
+                    style_border.right,
+                    style_border.bottom,
+                    style_border.top,
+                ));
+            }
+        }
+    }
+    if let Some(style_lerp) = style.color_lerp {
+        match color_lerp {
+            Some(mut color_lerp) => {
+                color_lerp.min = style_lerp.min;
+                color_lerp.max = style_lerp.max;
+            }
+            None => {
+                commands
+                    .entity(id)
+                    .insert(StatbarColorLerp::<T>::new(style_lerp.min, style_lerp.max));
+            }
+        }
+    }
+    if let Some(style_switch) = style.color_switch {
+        match color_switch {
+            Some(mut color_switch) => {
+                color_switch.pivot = style_switch.pivot;
+                color_switch.low = style_switch.low;
+                color_switch.high = style_switch.high;
+            }
+            None => {
+                commands.entity(id).insert(StatbarColorSwitch::<T>::new(
+                    style_switch.pivot,
+                    style_switch.low,
+                    style_switch.high,
+                ));
+            }
+        }
+    }
+}
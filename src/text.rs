@@ -0,0 +1,81 @@
+use crate::*;
+use std::marker::PhantomData;
+
+/// A statbar component that draws text on an entity of its own, like `StatbarLabel` and `StatbarTooltip`
+pub(crate) trait StatbarTextSource: Component {
+    fn text_entity(&self) -> Option<Entity>;
+
+    fn set_text_entity(&mut self, text_entity: Entity);
+
+    /// current and maximum value of the observed stat
+    fn current_max(&self) -> Option<(f32, f32)>;
+
+    fn set_current_max(&mut self, current_max: Option<(f32, f32)>);
+}
+
+/// Marks the entity that draws the text of the component `C` on the statbar `bar`
+#[derive(Component)]
+pub(crate) struct StatbarText<C>
+where
+    C: StatbarTextSource,
+{
+    pub(crate) bar: Entity,
+    /// the value and current and maximum values the text was last updated with
+    pub(crate) displayed: Option<(f32, Option<(f32, f32)>)>,
+    phantom: PhantomData<fn() -> C>,
+}
+
+/// Called by the systems that update statbar values.
+/// Only touches the component if the current and maximum values have changed.
+pub(crate) fn update_text_current_max<C>(source: Option<Mut<C>>, current_max: Option<(f32, f32)>)
+where
+    C: StatbarTextSource,
+{
+    if let Some(mut source) = source {
+        if source.current_max() != current_max {
+            source.set_current_max(current_max);
+        }
+    }
+}
+
+/// Spawns a hidden text entity for each new text component
+pub(crate) fn spawn_statbar_texts<C>(
+    mut commands: Commands,
+    mut source_query: Query<(Entity, &mut C), Changed<C>>,
+) where
+    C: StatbarTextSource,
+{
+    source_query.for_each_mut(|(bar, mut source)| {
+        if source.text_entity().is_none() {
+            let text_entity = commands
+                .spawn_bundle(Text2dBundle {
+                    visibility: Visibility { is_visible: false },
+                    ..Default::default()
+                })
+                .insert(StatbarText::<C> {
+                    bar,
+                    displayed: None,
+                    phantom: PhantomData,
+                })
+                .id();
+            source.set_text_entity(text_entity);
+        }
+    });
+}
+
+/// Despawns text entities whose statbar no longer draws its text with them
+pub(crate) fn despawn_orphaned_statbar_texts<C>(
+    mut commands: Commands,
+    text_query: Query<(Entity, &StatbarText<C>)>,
+    source_query: Query<&C>,
+) where
+    C: StatbarTextSource,
+{
+    despawn_orphans(
+        &mut commands,
+        &text_query,
+        &source_query,
+        |text| text.bar,
+        |source, text_entity| source.text_entity() == Some(text_entity),
+    );
+}
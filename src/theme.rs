@@ -0,0 +1,142 @@
+use crate::style::apply_style;
+use crate::*;
+use bevy::utils::HashMap;
+#[cfg(feature = "serialize")]
+use serde::Deserialize;
+#[cfg(feature = "serialize")]
+use serde::Serialize;
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+/// Default appearance for every statbar with a `StatbarUseTheme` component.
+///
+/// The style of a statbar is the theme's `style`, overridden by the style for its marker type,
+/// overridden by the style for its `StatbarFaction`.
+/// Statbars are restyled whenever the theme is changed.
+#[derive(Clone, Debug, Default)]
+pub struct StatbarTheme {
+    pub style: StatbarStyle,
+    /// font of the `StatbarLabel`s and `StatbarTooltip`s of themed statbars
+    pub font: Option<Handle<Font>>,
+    pub marker_styles: HashMap<TypeId, StatbarStyle>,
+    pub faction_styles: HashMap<String, StatbarStyle>,
+}
+
+impl StatbarTheme {
+    pub fn new(style: StatbarStyle) -> Self {
+        Self {
+            style,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_font(mut self, font: Handle<Font>) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    /// Overrides the style of statbars with the marker type `T`
+    pub fn with_marker_style<T: 'static>(mut self, style: StatbarStyle) -> Self {
+        self.marker_styles.insert(TypeId::of::<T>(), style);
+        self
+    }
+
+    /// Overrides the style of statbars on entities with a matching `StatbarFaction`
+    pub fn with_faction_style(mut self, faction: impl Into<String>, style: StatbarStyle) -> Self {
+        self.faction_styles.insert(faction.into(), style);
+        self
+    }
+
+    /// The style of a statbar with the marker type `T`
+    pub fn style_for<T: 'static>(&self, faction: Option<&str>) -> StatbarStyle {
+        let mut style = self.style.clone();
+        if let Some(marker_style) = self.marker_styles.get(&TypeId::of::<T>()) {
+            style = style.overridden_by(marker_style);
+        }
+        if let Some(faction_style) = faction.and_then(|faction| self.faction_styles.get(faction)) {
+            style = style.overridden_by(faction_style);
+        }
+        style
+    }
+}
+
+/// Tags an entity with a faction, used to pick the `StatbarTheme` faction style for all its statbars.
+#[derive(Clone, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct StatbarFaction(pub String);
+
+/// Opts the corresponding Statbar into the `StatbarTheme`.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarUseTheme<T>
+where
+    T: 'static,
+{
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarUseTheme<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_statbar_theme<T>(
+    mut commands: Commands,
+    theme: Option<Res<StatbarTheme>>,
+    mut theme_query: Query<
+        (
+            Entity,
+            Option<&StatbarFaction>,
+            &mut Statbar<T>,
+            Option<&mut StatbarBorder<T>>,
+            Option<&mut StatbarColorLerp<T>>,
+            Option<&mut StatbarColorSwitch<T>>,
+            Option<&mut StatbarLabel<T>>,
+            Option<&mut StatbarTooltip<T>>,
+        ),
+        With<StatbarUseTheme<T>>,
+    >,
+    changed_query: Query<(), Or<(Changed<StatbarUseTheme<T>>, Changed<StatbarFaction>)>>,
+) where
+    T: 'static,
+{
+    let theme = match theme {
+        Some(theme) => theme,
+        None => return,
+    };
+    let theme_changed = theme.is_changed();
+    theme_query.for_each_mut(
+        |(id, faction, mut bar, border, color_lerp, color_switch, label, tooltip)| {
+            if !theme_changed && changed_query.get(id).is_err() {
+                return;
+            }
+            let style = theme.style_for::<T>(faction.map(|faction| faction.0.as_str()));
+            apply_style(
+                &mut commands,
+                id,
+                &style,
+                &mut bar,
+                border,
+                color_lerp,
+                color_switch,
+            );
+            if let Some(font) = &theme.font {
+                if let Some(mut label) = label.filter(|label| &label.font != font) {
+                    label.font = font.clone();
+                }
+                if let Some(mut tooltip) = tooltip.filter(|tooltip| &tooltip.font != font) {
+                    tooltip.font = font.clone();
+                }
+            }
+        },
+    );
+}
@@ -0,0 +1,153 @@
+use crate::*;
+#[cfg(feature = "serialize")]
+use serde::Deserialize;
+#[cfg(feature = "serialize")]
+use serde::Serialize;
+use std::marker::PhantomData;
+
+/// The way the value of a statbar changed, reported by a `StatbarThresholdEvent`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StatbarCrossing {
+    /// the value was greater than the threshold and is now less than or equal to it
+    Fell(f32),
+    /// the value was less than or equal to the threshold and is now greater than it
+    Rose(f32),
+    /// the value reached 0.0
+    Empty,
+    /// the value reached 1.0
+    Full,
+}
+
+/// Sent when the value of a Statbar with a `StatbarThresholds` component
+/// crosses one of its thresholds, becomes empty, or becomes full.
+///
+/// The marker type `T` is the marker type of the Statbar, so systems can read
+/// the events for a single kind of statbar with `EventReader<StatbarThresholdEvent<Health>>`.
+#[derive(Clone, Copy, Debug)]
+pub struct StatbarThresholdEvent<T>
+where
+    T: 'static,
+{
+    /// the entity of the statbar
+    pub entity: Entity,
+    pub crossing: StatbarCrossing,
+    /// value of the statbar before it changed
+    pub old_value: f32,
+    /// value of the statbar after it changed
+    pub new_value: f32,
+    #[doc(hidden)]
+    pub _phantom: PhantomData<fn() -> T>,
+}
+
+/// Sends a `StatbarThresholdEvent` when the value of the corresponding Statbar
+/// crosses one of the thresholds, becomes empty or becomes full.
+///
+/// Thresholds are compared the same way as the pivot of `StatbarColorSwitch`,
+/// a value is below a threshold when it is less than or equal to it.
+#[derive(Clone, Debug, Component, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound = ""))]
+pub struct StatbarThresholds<T>
+where
+    T: 'static,
+{
+    pub thresholds: Vec<f32>,
+    /// value of the statbar when it was last checked, NaN before the first check
+    last_value: f32,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarThresholds<T>
+where
+    T: 'static,
+{
+    /// No thresholds, only sends empty and full events
+    fn default() -> Self {
+        Self::new(vec![])
+    }
+}
+
+impl<T> StatbarThresholds<T>
+where
+    T: 'static,
+{
+    pub fn new(thresholds: impl Into<Vec<f32>>) -> Self {
+        Self {
+            thresholds: thresholds.into(),
+            last_value: f32::NAN,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Every crossing from `old_value` to `new_value`, in the order they are passed through
+    pub fn crossings(&self, old_value: f32, new_value: f32) -> Vec<StatbarCrossing> {
+        let mut crossings: Vec<StatbarCrossing> = self
+            .thresholds
+            .iter()
+            .filter_map(|&threshold| {
+                if threshold < old_value && new_value <= threshold {
+                    Some(StatbarCrossing::Fell(threshold))
+                } else if old_value <= threshold && threshold < new_value {
+                    Some(StatbarCrossing::Rose(threshold))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        if new_value < old_value {
+            crossings.sort_by(|a, b| threshold_of(b).total_cmp(&threshold_of(a)));
+            if new_value <= 0. && 0. < old_value {
+                crossings.push(StatbarCrossing::Empty);
+            }
+        } else {
+            crossings.sort_by(|a, b| threshold_of(a).total_cmp(&threshold_of(b)));
+            if 1. <= new_value && old_value < 1. {
+                crossings.push(StatbarCrossing::Full);
+            }
+        }
+        crossings
+    }
+}
+
+fn threshold_of(crossing: &StatbarCrossing) -> f32 {
+    match *crossing {
+        StatbarCrossing::Fell(threshold) | StatbarCrossing::Rose(threshold) => threshold,
+        StatbarCrossing::Empty => 0.,
+        StatbarCrossing::Full => 1.,
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn send_statbar_threshold_events<T>(
+    mut threshold_query: Query<
+        (Entity, &Statbar<T>, &mut StatbarThresholds<T>),
+        Changed<Statbar<T>>,
+    >,
+    mut events: EventWriter<StatbarThresholdEvent<T>>,
+) where
+    T: 'static,
+{
+    threshold_query.for_each_mut(|(entity, bar, mut thresholds)| {
+        let new_value = bar.value.clamp(0., 1.);
+        let old_value = thresholds.last_value;
+        if old_value == new_value {
+            return;
+        }
+        thresholds.last_value = new_value;
+        if old_value.is_nan() {
+            return;
+        }
+        for crossing in thresholds.crossings(old_value, new_value) {
+            events.send(StatbarThresholdEvent {
+                entity,
+                crossing,
+                old_value,
+                new_value,
+                _phantom: PhantomData,
+            });
+        }
+    });
+}
@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use bevy_stat_bars::*;
+
+struct Hp;
+
+#[derive(Component)]
+struct Mana(f32);
+
+impl StatbarObservable for Mana {
+    fn get_statbar_value(&self) -> f32 {
+        self.0
+    }
+}
+
+impl StatbarSettable for Mana {
+    fn set_statbar_value(&mut self, value: f32) {
+        self.0 = value;
+    }
+}
+
+/// A headless app without the asset or render plugins
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(TransformPlugin)
+        .add_plugin(HierarchyPlugin)
+        .add_standalone_statbar::<Hp>()
+        .add_statbar_component_observer::<Mana>()
+        .add_statbar_component_slider::<Mana>();
+    app
+}
+
+#[test]
+fn headless_app_updates() {
+    let mut app = app();
+    app.world
+        .spawn()
+        .insert_bundle(SpatialBundle::default())
+        .insert_bundle((Statbar::<Mana>::default(), Mana(0.25)));
+    app.update();
+    app.update();
+
+    let bar = app.world.query::<&Statbar<Mana>>().single(&app.world);
+    assert_eq!(bar.value, 0.25);
+}
+
+#[test]
+fn color_rules_set_every_target() {
+    let mut app = app();
+    app.world
+        .spawn()
+        .insert_bundle(SpatialBundle::default())
+        .insert_bundle((
+            Statbar::<Hp> {
+                value: 0.25,
+                ..Default::default()
+            },
+            StatbarBorder::<Hp>::all(Color::RED, 1.),
+            StatbarColorSwitch::<Hp>::new(0.5, Color::ORANGE, Color::GREEN),
+            StatbarColorRules::<Hp>::new()
+                .with_switch(StatbarColorSwitch::border(0.5, Color::BLACK, Color::WHITE))
+                .with_switch(
+                    StatbarColorSwitch::new(0.1, Color::RED, Color::YELLOW).with_priority(1),
+                )
+                .with_lerp(
+                    StatbarColorLerp::new(Color::GRAY, Color::GRAY)
+                        .with_target(StatbarColorTarget::Empty),
+                ),
+        ));
+    app.update();
+
+    let (bar, border) = app
+        .world
+        .query::<(&Statbar<Hp>, &StatbarBorder<Hp>)>()
+        .single(&app.world);
+    assert_eq!(bar.color, Color::YELLOW);
+    assert_eq!(bar.empty_color, Color::GRAY);
+    assert_eq!(border.color, Color::BLACK);
+}
@@ -0,0 +1,276 @@
+use bevy::ecs::entity::EntityMap;
+use bevy::prelude::*;
+use bevy::reflect::TypeRegistryArc;
+use bevy::scene::serde::SceneDeserializer;
+use bevy_stat_bars::*;
+use serde::de::DeserializeSeed;
+
+struct Hp;
+
+/// A headless app with `Hp` statbars registered, the scenes are saved from its world
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_standalone_statbar::<Hp>();
+    app
+}
+
+/// Saves every entity in the app's world to a ron scene and loads it into a new world
+fn round_trip(app: &App) -> World {
+    let world = &app.world;
+    let registry = world.resource::<TypeRegistryArc>();
+    let ron = DynamicScene::from_world(world, registry)
+        .serialize_ron(registry)
+        .unwrap();
+    let mut deserializer = ron::de::Deserializer::from_str(&ron).unwrap();
+    let scene = SceneDeserializer {
+        type_registry: &registry.read(),
+    }
+    .deserialize(&mut deserializer)
+    .unwrap();
+    let mut loaded = World::new();
+    loaded.insert_resource(registry.clone());
+    scene
+        .write_to_world(&mut loaded, &mut EntityMap::default())
+        .unwrap();
+    loaded
+}
+
+#[test]
+fn statbar_round_trip() {
+    let mut app = app();
+    let world = &mut app.world;
+    world.spawn().insert(Statbar::<Hp> {
+        color: Color::RED,
+        empty_color: Color::NAVY,
+        length: 10.,
+        thickness: 2.,
+        displacement: 8. * Vec2::Y,
+        vertical: true,
+        reverse: true,
+        hide: true,
+        value: 0.25,
+        ..Default::default()
+    });
+
+    let mut loaded = round_trip(&app);
+    let bar = loaded.query::<&Statbar<Hp>>().single(&loaded);
+    assert_eq!(bar.color, Color::RED);
+    assert_eq!(bar.empty_color, Color::NAVY);
+    assert_eq!(bar.length, 10.);
+    assert_eq!(bar.thickness, 2.);
+    assert_eq!(bar.displacement, 8. * Vec2::Y);
+    assert!(bar.vertical);
+    assert!(bar.reverse);
+    assert!(bar.hide);
+    assert_eq!(bar.value, 0.25);
+}
+
+#[test]
+fn border_round_trip() {
+    let mut app = app();
+    let world = &mut app.world;
+    world.spawn().insert(
+        StatbarBorder::<Hp>::new(Color::WHITE, 1., 2., 3., 4.)
+            .with_side_colors(Color::GRAY, Color::GRAY, Color::BLACK, Color::WHITE)
+            .double(1., Color::NONE),
+    );
+
+    let mut loaded = round_trip(&app);
+    let border = loaded.query::<&StatbarBorder<Hp>>().single(&loaded);
+    assert_eq!(border.color, Color::WHITE);
+    assert_eq!(
+        (border.left, border.right, border.bottom, border.top),
+        (1., 2., 3., 4.)
+    );
+    assert_eq!(
+        border.side_colors(),
+        [Color::GRAY, Color::GRAY, Color::BLACK, Color::WHITE]
+    );
+    assert_eq!(
+        border.kind,
+        StatbarBorderKind::Double {
+            gap: 1.,
+            gap_color: Color::NONE
+        }
+    );
+}
+
+#[test]
+fn observe_entity_round_trip() {
+    let mut app = app();
+    let world = &mut app.world;
+    let target = world.spawn().insert(Statbar::<Hp>::default()).id();
+    world.spawn().insert(StatbarObserveEntity(target));
+
+    let mut loaded = round_trip(&app);
+    let loaded_target = loaded
+        .query_filtered::<Entity, With<Statbar<Hp>>>()
+        .single(&loaded);
+    let observe = loaded.query::<&StatbarObserveEntity>().single(&loaded);
+    assert_eq!(observe.0, loaded_target);
+}
+
+#[test]
+fn observe_entity_outside_scene_is_not_observed() {
+    let mut app = app();
+    let world = &mut app.world;
+    world
+        .spawn()
+        .insert(StatbarObserveEntity(Entity::from_raw(1000)));
+
+    let mut loaded = round_trip(&app);
+    let observe = loaded.query::<&StatbarObserveEntity>().single(&loaded);
+    assert_eq!(observe.0, Entity::from_raw(u32::MAX));
+}
+
+#[test]
+fn color_components_round_trip() {
+    let mut app = app();
+    let world = &mut app.world;
+    world.spawn().insert_bundle((
+        StatbarColorLerp::<Hp>::new(Color::RED, Color::GREEN)
+            .with_target(StatbarColorTarget::Empty)
+            .with_priority(1),
+        StatbarColorSwitch::<Hp>::border(0.5, Color::BLACK, Color::WHITE).with_priority(2),
+        StatbarColorRamp::<Hp>::fill(ColorRamp::even(
+            &[Color::RED, Color::YELLOW, Color::GREEN],
+            ColorInterpolation::Oklab,
+        ))
+        .with_priority(3),
+    ));
+
+    let mut loaded = round_trip(&app);
+    let (lerp, switch, ramp) = loaded
+        .query::<(
+            &StatbarColorLerp<Hp>,
+            &StatbarColorSwitch<Hp>,
+            &StatbarColorRamp<Hp>,
+        )>()
+        .single(&loaded);
+    assert_eq!((lerp.min, lerp.max), (Color::RED, Color::GREEN));
+    assert_eq!(lerp.target, StatbarColorTarget::Empty);
+    assert_eq!(lerp.priority, 1);
+    assert_eq!(switch.pivot, 0.5);
+    assert_eq!((switch.low, switch.high), (Color::BLACK, Color::WHITE));
+    assert_eq!(switch.target, StatbarColorTarget::Border);
+    assert_eq!(switch.priority, 2);
+    let positions: Vec<f32> = ramp.fill.stops().iter().map(|stop| stop.position).collect();
+    assert_eq!(positions, [0., 0.5, 1.]);
+    assert_eq!(ramp.fill.stops()[1].color, Color::YELLOW);
+    assert_eq!(ramp.fill.interpolation, ColorInterpolation::Oklab);
+    assert!(ramp.empty.is_empty());
+    assert_eq!(ramp.priority, 3);
+}
+
+#[test]
+fn color_rules_round_trip() {
+    let mut app = app();
+    app.world.spawn().insert(
+        StatbarColorRules::<Hp>::new()
+            .with_switch(StatbarColorSwitch::border(0.5, Color::BLACK, Color::WHITE))
+            .with_switch(StatbarColorSwitch::new(0.1, Color::RED, Color::NONE).with_priority(1))
+            .with_lerp(
+                StatbarColorLerp::new(Color::GRAY, Color::BLUE)
+                    .with_target(StatbarColorTarget::Empty),
+            ),
+    );
+
+    let mut loaded = round_trip(&app);
+    let rules = loaded.query::<&StatbarColorRules<Hp>>().single(&loaded);
+    assert_eq!(rules.switches.len(), 2);
+    assert_eq!(rules.switches[0].target, StatbarColorTarget::Border);
+    assert_eq!(rules.switches[1].pivot, 0.1);
+    assert_eq!(rules.switches[1].priority, 1);
+    assert_eq!(rules.lerps.len(), 1);
+    assert_eq!(
+        (rules.lerps[0].min, rules.lerps[0].max),
+        (Color::GRAY, Color::BLUE)
+    );
+    assert_eq!(rules.lerps[0].target, StatbarColorTarget::Empty);
+}
+
+#[test]
+fn observe_parent_round_trip() {
+    let mut app = app();
+    let world = &mut app.world;
+    let parent = world.spawn().id();
+    let child = world
+        .spawn()
+        .insert_bundle((Statbar::<Hp>::default(), StatbarObserveParent))
+        .id();
+    world.entity_mut(parent).push_children(&[child]);
+
+    let mut loaded = round_trip(&app);
+    assert_eq!(
+        loaded
+            .query_filtered::<(), (With<Statbar<Hp>>, With<StatbarObserveParent>)>()
+            .iter(&loaded)
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn depth_offset_round_trip() {
+    let mut app = app();
+    app.world
+        .spawn()
+        .insert(StatbarDepthOffset::<Hp>::relative(2.5));
+
+    let mut loaded = round_trip(&app);
+    let depth_offset = loaded.query::<&StatbarDepthOffset<Hp>>().single(&loaded);
+    assert_eq!(depth_offset.offset, 2.5);
+    assert!(depth_offset.relative);
+}
+
+#[test]
+fn effect_components_round_trip() {
+    let mut app = app();
+    app.world.spawn().insert_bundle((
+        StatbarFlash::<Hp>::new(Color::WHITE, 0.2),
+        StatbarPulse::<Hp>::new(0.25, Color::RED, 2.),
+        StatbarShake::<Hp>::new(0.1, 3., 0.5),
+    ));
+
+    let mut loaded = round_trip(&app);
+    let (flash, pulse, shake) = loaded
+        .query::<(&StatbarFlash<Hp>, &StatbarPulse<Hp>, &StatbarShake<Hp>)>()
+        .single(&loaded);
+    assert_eq!((flash.color, flash.duration), (Color::WHITE, 0.2));
+    assert_eq!(
+        (pulse.threshold, pulse.color, pulse.frequency),
+        (0.25, Color::RED, 2.)
+    );
+    assert_eq!(
+        (shake.min_decrease, shake.magnitude, shake.duration),
+        (0.1, 3., 0.5)
+    );
+}
+
+#[test]
+fn decoration_components_round_trip() {
+    let mut app = app();
+    app.world.spawn().insert_bundle((
+        StatbarPattern::<Hp>::new(StatbarPatternKind::Dots)
+            .below(0.3)
+            .with_color(Color::BLACK),
+        StatbarShadow::<Hp>::new(Vec2::new(1., -2.), 3.).with_color(Color::BLUE),
+        StatbarGlow::<Hp>::new(Color::YELLOW, 4.).fade_in(0.5, 0.9),
+    ));
+
+    let mut loaded = round_trip(&app);
+    let (pattern, shadow, glow) = loaded
+        .query::<(&StatbarPattern<Hp>, &StatbarShadow<Hp>, &StatbarGlow<Hp>)>()
+        .single(&loaded);
+    assert_eq!(pattern.kind, StatbarPatternKind::Dots);
+    assert_eq!((pattern.threshold, pattern.color), (0.3, Color::BLACK));
+    assert_eq!(
+        (shadow.offset, shadow.blur, shadow.color),
+        (Vec2::new(1., -2.), 3., Color::BLUE)
+    );
+    assert_eq!(
+        (glow.color, glow.radius, glow.start, glow.full),
+        (Color::YELLOW, 4., 0.5, 0.9)
+    );
+}
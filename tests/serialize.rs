@@ -0,0 +1,90 @@
+#![cfg(feature = "serialize")]
+
+use bevy::prelude::*;
+use bevy_stat_bars::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+struct Hp;
+
+/// Serializes `value` to ron, deserializes it and checks that it serializes to the same ron again
+fn round_trip<S>(value: &S) -> S
+where
+    S: Serialize + DeserializeOwned,
+{
+    let ron = ron::to_string(value).unwrap();
+    let loaded: S = ron::from_str(&ron).unwrap();
+    assert_eq!(ron::to_string(&loaded).unwrap(), ron);
+    loaded
+}
+
+#[test]
+fn components_round_trip() {
+    round_trip(&Statbar::<Hp> {
+        color: Color::RED,
+        value: 0.25,
+        ..Default::default()
+    });
+    round_trip(
+        &StatbarBorder::<Hp>::all(Color::WHITE, 2.)
+            .with_side_colors(Color::GRAY, Color::GRAY, Color::BLACK, Color::WHITE)
+            .double(1., Color::NONE),
+    );
+    round_trip(&StatbarColorLerp::<Hp>::new(Color::RED, Color::GREEN));
+    round_trip(&StatbarColorSwitch::<Hp>::new(
+        0.5,
+        Color::RED,
+        Color::GREEN,
+    ));
+    round_trip(&StatbarColorRamp::<Hp>::fill(ColorRamp::from_palette(
+        StatbarPalette::ORANGE_BLUE,
+        ColorInterpolation::Oklab,
+    )));
+    round_trip(&StatbarDepthOffset::<Hp>::relative(1.));
+    round_trip(&StatbarPattern::<Hp>::new(StatbarPatternKind::Dots));
+    round_trip(&StatbarShadow::<Hp>::default());
+    round_trip(&StatbarGlow::<Hp>::default());
+    round_trip(&StatbarFlash::<Hp>::new(Color::WHITE, 0.2));
+    round_trip(&StatbarAutoHide::<Hp>::default());
+    round_trip(&StatbarLod::<Hp>::default());
+    round_trip(&StatbarDeclutter::<Hp>::default());
+    round_trip(&StatbarFillEffect::<Hp>::default());
+}
+
+#[test]
+fn settings_round_trip() {
+    round_trip(&StatbarDepth(500.));
+    round_trip(&StatbarDeclutterSettings::default());
+    round_trip(&StatbarLodSettings {
+        cull_distance: 100.,
+        ..Default::default()
+    });
+    round_trip(&StatbarEdgeMode::PixelSnap);
+    round_trip(&StatbarColorblindMode::Daltonize(
+        ColorVisionDeficiency::Deuteranopia,
+    ));
+    round_trip(&StatbarPalette::ORANGE_BLUE);
+}
+
+#[test]
+fn camera_entities_are_not_serialized() {
+    let clamp = StatbarEdgeClamp::<Hp>::new(4.).with_camera(Entity::from_raw(7));
+    assert_eq!(round_trip(&clamp).camera, None);
+}
+
+#[test]
+fn color_ramp_stops_are_sorted_when_loaded() {
+    let ramp = ColorRamp::new(
+        [
+            ColorStop::new(0., Color::RED),
+            ColorStop::new(1., Color::GREEN),
+        ],
+        ColorInterpolation::LinearRgb,
+    );
+    let ron = ron::to_string(&ramp).unwrap();
+    let reversed = ron.replacen("position:0.0", "position:2.0", 1);
+    assert_ne!(reversed, ron);
+    let loaded: ColorRamp = ron::from_str(&reversed).unwrap();
+    let positions: Vec<f32> = loaded.stops().iter().map(|stop| stop.position).collect();
+    assert_eq!(positions, [1., 2.]);
+}